@@ -19,25 +19,29 @@ impl Drop for HotPlugHandler {
 }
 
 fn main() -> rusb::Result<()> {
-    if rusb::has_hotplug() {
-        let context = Context::new()?;
+    let context = Context::new()?;
 
-        let mut reg = Some(
-            HotplugBuilder::new()
-                .enumerate(true)
-                .register(&context, Box::new(HotPlugHandler {}))?,
-        );
+    // Registering fails with `NotSupported` where `libusb` has no hotplug support. Unlike
+    // `rusb::has_hotplug`, this needs no global context.
+    let reg = match HotplugBuilder::new()
+        .enumerate(true)
+        .register(&context, Box::new(HotPlugHandler {}))
+    {
+        Ok(reg) => reg,
+        Err(rusb::Error::NotSupported) => {
+            eprint!("libusb hotplug api unsupported");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
 
-        loop {
-            context.handle_events(None).unwrap();
-            if let Some(reg) = reg.take() {
-                context.unregister_callback(reg);
-                break;
-            }
+    let mut reg = Some(reg);
+    loop {
+        context.handle_events(None).unwrap();
+        if let Some(reg) = reg.take() {
+            context.unregister_callback(reg);
+            break;
         }
-        Ok(())
-    } else {
-        eprint!("libusb hotplug api unsupported");
-        Ok(())
     }
+    Ok(())
 }
@@ -1,4 +1,9 @@
-use rusb::UsbContext;
+use rusb::{constants::*, UsbContext};
+
+/// Asks `libusb` directly, as the `rusb::has_*` functions need the global context.
+fn has_capability(capability: u32) -> bool {
+    unsafe { rusb::ffi::libusb_has_capability(capability) != 0 }
+}
 
 fn main() {
     let version = rusb::version();
@@ -23,11 +28,17 @@ fn main() {
     context.set_log_level(rusb::LogLevel::Error);
     context.set_log_level(rusb::LogLevel::None);
 
-    println!("has capability? {}", rusb::has_capability());
-    println!("has hotplug? {}", rusb::has_hotplug());
-    println!("has HID access? {}", rusb::has_hid_access());
+    println!(
+        "has capability? {}",
+        has_capability(LIBUSB_CAP_HAS_CAPABILITY)
+    );
+    println!("has hotplug? {}", has_capability(LIBUSB_CAP_HAS_HOTPLUG));
+    println!(
+        "has HID access? {}",
+        has_capability(LIBUSB_CAP_HAS_HID_ACCESS)
+    );
     println!(
         "supports detach kernel driver? {}",
-        rusb::supports_detach_kernel_driver()
+        has_capability(LIBUSB_CAP_SUPPORTS_DETACH_KERNEL_DRIVER)
     )
 }
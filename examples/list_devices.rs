@@ -19,27 +19,16 @@ fn main() {
 fn list_devices() -> Result<()> {
     let timeout = Duration::from_secs(1);
 
-    for device in DeviceList::new()?.iter() {
-        let device_desc = match device.device_descriptor() {
-            Ok(d) => d,
-            Err(_) => continue,
-        };
-
+    for (device, device_desc) in DeviceList::new()?.iter_with_descriptors() {
         let mut usb_device = {
             match device.open() {
-                Ok(h) => match h.read_languages(timeout) {
-                    Ok(l) => {
-                        if !l.is_empty() {
-                            Some(UsbDevice {
-                                handle: h,
-                                language: l[0],
-                                timeout,
-                            })
-                        } else {
-                            None
-                        }
-                    }
-                    Err(_) => None,
+                Ok(h) => match h.preferred_language(timeout) {
+                    Ok(Some(language)) => Some(UsbDevice {
+                        handle: h,
+                        language,
+                        timeout,
+                    }),
+                    _ => None,
                 },
                 Err(_) => None,
             }
@@ -55,8 +44,8 @@ fn list_devices() -> Result<()> {
         );
         print_device(&device_desc, &mut usb_device);
 
-        for n in 0..device_desc.num_configurations() {
-            let config_desc = match device.config_descriptor(n) {
+        for config_desc in device.config_descriptors() {
+            let config_desc = match config_desc {
                 Ok(c) => c,
                 Err(_) => continue,
             };
@@ -97,10 +86,8 @@ fn print_device<T: UsbContext>(device_desc: &DeviceDescriptor, handle: &mut Opti
     println!("  bLength              {:3}", device_desc.length());
     println!("  bDescriptorType      {:3}", device_desc.descriptor_type());
     println!(
-        "  bcdUSB             {:2}.{}{}",
-        device_desc.usb_version().major(),
-        device_desc.usb_version().minor(),
-        device_desc.usb_version().sub_minor()
+        "  bcdUSB             {:>5}",
+        device_desc.usb_version().to_lsusb_string()
     );
     println!("  bDeviceClass        {:#04x}", device_desc.class_code());
     println!(
@@ -112,10 +99,8 @@ fn print_device<T: UsbContext>(device_desc: &DeviceDescriptor, handle: &mut Opti
     println!("  idVendor          {vid:#06x} {vendor_name}",);
     println!("  idProduct         {pid:#06x} {product_name}",);
     println!(
-        "  bcdDevice          {:2}.{}{}",
-        device_desc.device_version().major(),
-        device_desc.device_version().minor(),
-        device_desc.device_version().sub_minor()
+        "  bcdDevice          {:>5}",
+        device_desc.device_version().to_lsusb_string()
     );
     println!(
         "  iManufacturer        {:3} {}",
@@ -1,7 +1,8 @@
 use std::time::Duration;
 
 use rusb::{
-    Context, Device, DeviceDescriptor, DeviceHandle, Direction, Result, TransferType, UsbContext,
+    Context, Device, DeviceDescriptor, DeviceHandle, Direction, ResetOutcome, Result, TransferType,
+    UsbContext,
 };
 
 #[derive(Debug)]
@@ -74,7 +75,10 @@ fn read_device<T: UsbContext>(
     device_desc: &DeviceDescriptor,
     handle: &mut DeviceHandle<T>,
 ) -> Result<()> {
-    handle.reset()?;
+    if handle.reset()? == ResetOutcome::Reenumerated {
+        println!("Device re-enumerated after reset, open it again");
+        return Ok(());
+    }
 
     let timeout = Duration::from_secs(1);
     let languages = handle.read_languages(timeout)?;
@@ -82,9 +86,7 @@ fn read_device<T: UsbContext>(
     println!("Active configuration: {}", handle.active_configuration()?);
     println!("Languages: {:?}", languages);
 
-    if !languages.is_empty() {
-        let language = languages[0];
-
+    if let Some(language) = handle.preferred_language(timeout)? {
         println!(
             "Manufacturer: {:?}",
             handle
@@ -0,0 +1,59 @@
+use std::{thread, time::Duration};
+
+use rusb::{Context, Direction, Recipient, RequestType, UsbContext};
+
+fn main() {
+    let context = match Context::with_event_thread() {
+        Ok(context) => context,
+        Err(e) => panic!("Context::with_event_thread(): {}", e),
+    };
+
+    let devices = match context.devices() {
+        Ok(devices) => devices,
+        Err(e) => panic!("devices(): {}", e),
+    };
+
+    // One worker thread per device, each with its own handle. The event thread of `context`
+    // handles events for all of them.
+    let workers: Vec<_> = devices
+        .iter()
+        .map(|device| {
+            thread::spawn(move || {
+                let name = format!(
+                    "Bus {:03} Device {:03}",
+                    device.bus_number(),
+                    device.address()
+                );
+
+                let handle = match device.open() {
+                    Ok(handle) => handle,
+                    Err(e) => return format!("{}: can't open: {}", name, e),
+                };
+
+                let mut status = [0; 2];
+                match handle.read_control(
+                    rusb::request_type(Direction::In, RequestType::Standard, Recipient::Device),
+                    rusb::constants::LIBUSB_REQUEST_GET_STATUS,
+                    0,
+                    0,
+                    &mut status,
+                    Duration::from_secs(1),
+                ) {
+                    Ok(_) => format!("{}: status {:#06x}", name, u16::from_le_bytes(status)),
+                    Err(e) => format!("{}: GET_STATUS failed: {}", name, e),
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        println!("{}", worker.join().unwrap());
+    }
+
+    // The devices were the last clones besides `context`, so the event thread is stopped and
+    // the context closed here.
+    drop(devices);
+    if context.shutdown_blocking().is_err() {
+        println!("context still in use");
+    }
+}
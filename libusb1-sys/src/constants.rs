@@ -61,6 +61,7 @@ pub const LIBUSB_CLASS_VIDEO: u8 = 0x0E;
 pub const LIBUSB_CLASS_PERSONAL_HEALTHCARE: u8 = 0x0F;
 pub const LIBUSB_CLASS_DIAGNOSTIC_DEVICE: u8 = 0xDC;
 pub const LIBUSB_CLASS_WIRELESS: u8 = 0xE0;
+pub const LIBUSB_CLASS_MISCELLANEOUS: u8 = 0xEF;
 pub const LIBUSB_CLASS_APPLICATION: u8 = 0xFE;
 pub const LIBUSB_CLASS_VENDOR_SPEC: u8 = 0xFF;
 
@@ -78,6 +79,7 @@ pub const LIBUSB_DT_CONFIG: u8 = 0x02;
 pub const LIBUSB_DT_STRING: u8 = 0x03;
 pub const LIBUSB_DT_INTERFACE: u8 = 0x04;
 pub const LIBUSB_DT_ENDPOINT: u8 = 0x05;
+pub const LIBUSB_DT_INTERFACE_ASSOCIATION: u8 = 0x0B;
 pub const LIBUSB_DT_BOS: u8 = 0x0F;
 pub const LIBUSB_DT_DEVICE_CAPABILITY: u8 = 0x10;
 pub const LIBUSB_DT_HID: u8 = 0x21;
@@ -87,6 +89,17 @@ pub const LIBUSB_DT_HUB: u8 = 0x29;
 pub const LIBUSB_DT_SUPERSPEED_HUB: u8 = 0x2A;
 pub const LIBUSB_DT_SS_ENDPOINT_COMPANION: u8 = 0x30;
 
+// descriptor sizes per descriptor type
+pub const LIBUSB_DT_DEVICE_SIZE: u8 = 18;
+pub const LIBUSB_DT_CONFIG_SIZE: u8 = 9;
+pub const LIBUSB_DT_INTERFACE_SIZE: u8 = 9;
+pub const LIBUSB_DT_ENDPOINT_SIZE: u8 = 7;
+pub const LIBUSB_DT_ENDPOINT_AUDIO_SIZE: u8 = 9;
+pub const LIBUSB_DT_HUB_NONVAR_SIZE: u8 = 7;
+pub const LIBUSB_DT_SS_ENDPOINT_COMPANION_SIZE: u8 = 6;
+pub const LIBUSB_DT_BOS_SIZE: u8 = 5;
+pub const LIBUSB_DT_DEVICE_CAPABILITY_SIZE: u8 = 3;
+
 // libusb_endpoint_direction
 pub const LIBUSB_ENDPOINT_ADDRESS_MASK: u8 = 0x0F;
 pub const LIBUSB_ENDPOINT_DIR_MASK: u8 = 0x80;
@@ -0,0 +1,162 @@
+use std::{fmt, time::Duration};
+
+use crate::usb_io::UsbIo;
+
+/// Size of a single read issued by [`BulkFrames`] when frames are small.
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Iterator over fixed-size frames read from a bulk endpoint.
+///
+/// Created by [`DeviceHandle::bulk_frames`](crate::DeviceHandle::bulk_frames). Each call to
+/// `next()` returns one frame of exactly `frame_size` bytes. Data is read in chunks spanning
+/// several frames, and bytes of an incomplete frame are kept until the following reads complete
+/// it.
+///
+/// The iterator never ends on its own. A failed read is yielded as an `Err` item without
+/// discarding buffered data, so iteration may continue after a timeout.
+pub struct BulkFrames<'a> {
+    io: &'a dyn UsbIo,
+    endpoint: u8,
+    frame_size: usize,
+    chunk_size: usize,
+    timeout: Duration,
+    pending: Vec<u8>,
+}
+
+impl<'a> BulkFrames<'a> {
+    pub(crate) fn new(
+        io: &'a dyn UsbIo,
+        endpoint: u8,
+        frame_size: usize,
+        timeout: Duration,
+    ) -> BulkFrames<'a> {
+        let chunk_size = chunk_size(frame_size);
+
+        BulkFrames {
+            io,
+            endpoint,
+            frame_size,
+            chunk_size,
+            timeout,
+            pending: Vec::with_capacity(chunk_size + frame_size),
+        }
+    }
+
+    /// Returns the bytes that were read but don't make up a whole frame yet.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+}
+
+impl<'a> Iterator for BulkFrames<'a> {
+    type Item = crate::Result<Vec<u8>>;
+
+    /// Returns the next frame, or `None` if the frame size is zero.
+    fn next(&mut self) -> Option<crate::Result<Vec<u8>>> {
+        if self.frame_size == 0 {
+            return None;
+        }
+
+        let (io, endpoint, timeout) = (self.io, self.endpoint, self.timeout);
+        Some(next_frame(
+            &mut self.pending,
+            self.frame_size,
+            self.chunk_size,
+            |buf| io.read_bulk(endpoint, buf, timeout),
+        ))
+    }
+}
+
+/// Returns the size of the reads for frames of `frame_size` bytes: a whole number of frames.
+fn chunk_size(frame_size: usize) -> usize {
+    match frame_size {
+        0 => 0,
+        n => n * (READ_CHUNK_SIZE / n).max(1),
+    }
+}
+
+/// Reads chunks of `chunk_size` bytes into `pending` until it holds a frame, and takes that
+/// frame from it. A failed read leaves the bytes read before it in `pending`.
+fn next_frame<F>(
+    pending: &mut Vec<u8>,
+    frame_size: usize,
+    chunk_size: usize,
+    mut read: F,
+) -> crate::Result<Vec<u8>>
+where
+    F: FnMut(&mut [u8]) -> crate::Result<usize>,
+{
+    while pending.len() < frame_size {
+        let filled = pending.len();
+        pending.resize(filled + chunk_size, 0);
+
+        match read(&mut pending[filled..]) {
+            Ok(n) => pending.truncate(filled + n),
+            Err(e) => {
+                pending.truncate(filled);
+                return Err(e);
+            }
+        }
+    }
+
+    let rest = pending.split_off(frame_size);
+    Ok(std::mem::replace(pending, rest))
+}
+
+impl<'a> fmt::Debug for BulkFrames<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BulkFrames")
+            .field("endpoint", &self.endpoint)
+            .field("frame_size", &self.frame_size)
+            .field("timeout", &self.timeout)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chunk_size, next_frame};
+    use crate::{test_helpers::reads, Error};
+
+    #[test]
+    fn it_splits_reads_into_frames() {
+        let mut read = reads(vec![Ok(vec![1, 2, 3, 4, 5, 6])]);
+        let mut pending = Vec::new();
+
+        for frame in [[1, 2], [3, 4], [5, 6]].iter() {
+            let next = next_frame(&mut pending, 2, chunk_size(2), &mut read);
+            assert_eq!(next, Ok(frame.to_vec()));
+        }
+    }
+
+    #[test]
+    fn it_carries_partial_frames_across_reads() {
+        let mut read = reads(vec![Ok(vec![1, 2, 3]), Ok(vec![4]), Ok(vec![5, 6, 7, 8])]);
+        let mut pending = Vec::new();
+
+        let next = next_frame(&mut pending, 4, chunk_size(4), &mut read);
+        assert_eq!(next, Ok(vec![1, 2, 3, 4]));
+        let next = next_frame(&mut pending, 4, chunk_size(4), &mut read);
+        assert_eq!(next, Ok(vec![5, 6, 7, 8]));
+    }
+
+    #[test]
+    fn it_keeps_pending_bytes_after_an_error() {
+        let mut read = reads(vec![Ok(vec![1, 2, 3]), Err(Error::Timeout), Ok(vec![4])]);
+        let mut pending = Vec::new();
+
+        let next = next_frame(&mut pending, 4, chunk_size(4), &mut read);
+        assert_eq!(next, Err(Error::Timeout));
+        assert_eq!(pending, [1, 2, 3]);
+        let next = next_frame(&mut pending, 4, chunk_size(4), &mut read);
+        assert_eq!(next, Ok(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn it_reads_whole_frames_at_once() {
+        assert_eq!(chunk_size(0), 0);
+        assert_eq!(chunk_size(1000) % 1000, 0);
+        assert_eq!(chunk_size(64 * 1024), 64 * 1024);
+    }
+}
@@ -1,18 +1,23 @@
-use std::{fmt, slice};
+use std::{any::Any, fmt, slice, sync::OnceLock};
 
-use libusb1_sys::*;
+use libusb1_sys::{constants::*, *};
 
 use crate::interface_descriptor::{self, Interface};
 
 /// Describes a configuration.
 pub struct ConfigDescriptor {
     descriptor: *const libusb_config_descriptor,
+    raw: OnceLock<Vec<u8>>,
+    // Holds the descriptors if they weren't allocated by libusb.
+    owner: Option<Box<dyn Any + Send + Sync>>,
 }
 
 impl Drop for ConfigDescriptor {
     fn drop(&mut self) {
-        unsafe {
-            libusb_free_config_descriptor(self.descriptor);
+        if self.owner.is_none() {
+            unsafe {
+                libusb_free_config_descriptor(self.descriptor);
+            }
         }
     }
 }
@@ -42,11 +47,18 @@ impl ConfigDescriptor {
     }
 
     /// Returns the device's maximum power consumption (in milliamps) in this configuration.
+    ///
+    /// This uses the 2 mA units of `bMaxPower` for devices up to high speed. SuperSpeed devices
+    /// count in 8 mA units, see [`Device::max_power_draw`](crate::Device::max_power_draw).
     pub fn max_power(&self) -> u16 {
         unsafe { u16::from((*self.descriptor).bMaxPower) * 2 }
     }
 
     /// Indicates if the device is self-powered in this configuration.
+    ///
+    /// This is the capability advertised in `bmAttributes`. Whether the device actually runs
+    /// on its own supply right now is reported by
+    /// [`DeviceHandle::is_self_powered`](crate::DeviceHandle::is_self_powered).
     pub fn self_powered(&self) -> bool {
         unsafe { (*self.descriptor).bmAttributes & 0x40 != 0 }
     }
@@ -94,6 +106,158 @@ impl ConfigDescriptor {
             }
         }
     }
+
+    /// Returns the configuration's interface association descriptors.
+    ///
+    /// Composite devices use interface associations to group several interfaces into one
+    /// function, e.g. the control and streaming interfaces of a video camera. libusb does not
+    /// parse them, so they are looked up in the [`raw`](#method.raw) configuration.
+    pub fn interface_associations(&self) -> Vec<InterfaceAssociation> {
+        descriptors(self.raw())
+            .filter(|descriptor| {
+                descriptor[1] == LIBUSB_DT_INTERFACE_ASSOCIATION && descriptor.len() >= 8
+            })
+            .map(|descriptor| InterfaceAssociation {
+                first_interface: descriptor[2],
+                interface_count: descriptor[3],
+                function_class: descriptor[4],
+                function_sub_class: descriptor[5],
+                function_protocol: descriptor[6],
+                function_string_index: descriptor[7],
+            })
+            .collect()
+    }
+
+    /// Returns the first descriptor of type `descriptor_type` in the configuration.
+    ///
+    /// The whole [`raw`](#method.raw) configuration is searched, so this finds class-specific
+    /// descriptors that follow the configuration, an interface or an endpoint. The returned
+    /// slice is the complete descriptor starting with `bLength`, so offsets from class
+    /// specifications apply to it directly.
+    pub fn find_descriptor(&self, descriptor_type: u8) -> Option<&[u8]> {
+        descriptors(self.raw()).find(|descriptor| descriptor[1] == descriptor_type)
+    }
+
+    /// Returns the first class-specific descriptor of type `descriptor_type` whose subtype, the
+    /// byte following `bDescriptorType`, is `subtype`.
+    ///
+    /// Class specifications reuse a few descriptor types for many descriptors, e.g. 0x24 for
+    /// every interface descriptor of UVC, UAC and CDC, and tell them apart by the subtype. The
+    /// returned slice is the complete descriptor, as for
+    /// [`find_descriptor`](#method.find_descriptor).
+    pub fn find_cs_descriptor(&self, descriptor_type: u8, subtype: u8) -> Option<&[u8]> {
+        descriptors(self.raw()).find(|descriptor| {
+            descriptor[1] == descriptor_type && descriptor.get(2) == Some(&subtype)
+        })
+    }
+
+    /// Returns the whole configuration in wire layout: the configuration descriptor followed by
+    /// every interface, endpoint, and class-specific descriptor.
+    ///
+    /// libusb does not keep the bytes it read from the device, so this is a reserialization of
+    /// the parsed descriptors and their 'extra' bytes, built on first use and cached afterwards.
+    /// Standard descriptors are written at their standard length, 9 bytes for the configuration
+    /// and interfaces and 7 or 9 for endpoints, with `bLength` set to match. For a well-formed
+    /// configuration this equals the bytes the device sent; use
+    /// [`DeviceHandle::read_config_descriptor_raw`](crate::DeviceHandle::read_config_descriptor_raw)
+    /// to get those exactly.
+    pub fn raw(&self) -> &[u8] {
+        self.raw
+            .get_or_init(|| unsafe { serialize_config(&*self.descriptor) })
+    }
+}
+
+/// Iterates over the descriptors in `bytes`, each at least two bytes long, stopping at the first
+/// one with an invalid length.
+fn descriptors(mut bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    std::iter::from_fn(move || {
+        let len = usize::from(*bytes.first()?);
+        if len < 2 || len > bytes.len() {
+            return None;
+        }
+
+        let (descriptor, rest) = bytes.split_at(len);
+        bytes = rest;
+        Some(descriptor)
+    })
+}
+
+unsafe fn extra_bytes<'a>(extra: *const u8, extra_length: libc::c_int) -> &'a [u8] {
+    match extra_length {
+        len if len > 0 => slice::from_raw_parts(extra, len as usize),
+        _ => &[],
+    }
+}
+
+/// Reassembles the configuration from the parsed descriptors.
+///
+/// Standard descriptors are written with the fields libusb parsed, so each gets the `bLength`
+/// of what is written rather than the one the device reported, keeping the blob walkable.
+unsafe fn serialize_config(config: &libusb_config_descriptor) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(config.wTotalLength as usize);
+
+    raw.extend_from_slice(&[9, config.bDescriptorType]);
+    raw.extend_from_slice(&config.wTotalLength.to_le_bytes());
+    raw.extend_from_slice(&[
+        config.bNumInterfaces,
+        config.bConfigurationValue,
+        config.iConfiguration,
+        config.bmAttributes,
+        config.bMaxPower,
+    ]);
+    raw.extend_from_slice(extra_bytes(config.extra, config.extra_length));
+
+    let interfaces = if config.interface.is_null() {
+        &[][..]
+    } else {
+        slice::from_raw_parts(config.interface, config.bNumInterfaces as usize)
+    };
+    for interface in interfaces {
+        let settings = if interface.altsetting.is_null() {
+            &[][..]
+        } else {
+            slice::from_raw_parts(interface.altsetting, interface.num_altsetting as usize)
+        };
+        for setting in settings {
+            raw.extend_from_slice(&[
+                9,
+                setting.bDescriptorType,
+                setting.bInterfaceNumber,
+                setting.bAlternateSetting,
+                setting.bNumEndpoints,
+                setting.bInterfaceClass,
+                setting.bInterfaceSubClass,
+                setting.bInterfaceProtocol,
+                setting.iInterface,
+            ]);
+            raw.extend_from_slice(extra_bytes(setting.extra, setting.extra_length));
+
+            let endpoints = if setting.endpoint.is_null()
+                || setting.bNumEndpoints > interface_descriptor::MAX_ENDPOINTS
+            {
+                &[][..]
+            } else {
+                slice::from_raw_parts(setting.endpoint, setting.bNumEndpoints as usize)
+            };
+            for endpoint in endpoints {
+                // Audio endpoints are 9 bytes long, all others 7.
+                let len = (endpoint.bLength as usize).clamp(7, 9);
+                let mut bytes = [0u8; 9];
+                bytes[0] = len as u8;
+                bytes[1] = endpoint.bDescriptorType;
+                bytes[2] = endpoint.bEndpointAddress;
+                bytes[3] = endpoint.bmAttributes;
+                bytes[4..6].copy_from_slice(&endpoint.wMaxPacketSize.to_le_bytes());
+                bytes[6] = endpoint.bInterval;
+                bytes[7] = endpoint.bRefresh;
+                bytes[8] = endpoint.bSynchAddress;
+                raw.extend_from_slice(&bytes[..len]);
+                raw.extend_from_slice(extra_bytes(endpoint.extra, endpoint.extra_length));
+            }
+        }
+    }
+
+    raw
 }
 
 impl fmt::Debug for ConfigDescriptor {
@@ -116,6 +280,58 @@ impl fmt::Debug for ConfigDescriptor {
     }
 }
 
+/// Describes a group of interfaces that form a single function of a composite device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceAssociation {
+    first_interface: u8,
+    interface_count: u8,
+    function_class: u8,
+    function_sub_class: u8,
+    function_protocol: u8,
+    function_string_index: u8,
+}
+
+impl InterfaceAssociation {
+    /// Returns the number of the first interface of the function.
+    pub fn first_interface(&self) -> u8 {
+        self.first_interface
+    }
+
+    /// Returns the number of contiguous interfaces that belong to the function.
+    pub fn interface_count(&self) -> u8 {
+        self.interface_count
+    }
+
+    /// Returns the numbers of the interfaces that belong to the function.
+    pub fn interfaces(&self) -> std::ops::Range<u16> {
+        let first = u16::from(self.first_interface);
+        first..first + u16::from(self.interface_count)
+    }
+
+    /// Returns the function's class code.
+    pub fn function_class(&self) -> u8 {
+        self.function_class
+    }
+
+    /// Returns the function's sub class code.
+    pub fn function_sub_class(&self) -> u8 {
+        self.function_sub_class
+    }
+
+    /// Returns the function's protocol code.
+    pub fn function_protocol(&self) -> u8 {
+        self.function_protocol
+    }
+
+    /// Returns the index of the string descriptor that describes the function.
+    pub fn function_string_index(&self) -> Option<u8> {
+        match self.function_string_index {
+            0 => None,
+            n => Some(n),
+        }
+    }
+}
+
 /// Iterator over a configuration's interfaces.
 pub struct Interfaces<'a> {
     iter: slice::Iter<'a, libusb_interface>,
@@ -137,7 +353,24 @@ impl<'a> Iterator for Interfaces<'a> {
 
 #[doc(hidden)]
 pub(crate) unsafe fn from_libusb(config: *const libusb_config_descriptor) -> ConfigDescriptor {
-    ConfigDescriptor { descriptor: config }
+    ConfigDescriptor {
+        descriptor: config,
+        raw: OnceLock::new(),
+        owner: None,
+    }
+}
+
+/// Wraps a configuration descriptor that `owner` keeps alive, instead of one to be freed by
+/// libusb. `config` and everything it points to must stay valid as long as `owner` exists.
+pub(crate) unsafe fn from_owned(
+    config: *const libusb_config_descriptor,
+    owner: Box<dyn Any + Send + Sync>,
+) -> ConfigDescriptor {
+    ConfigDescriptor {
+        descriptor: config,
+        raw: OnceLock::new(),
+        owner: Some(owner),
+    }
 }
 
 #[cfg(test)]
@@ -231,6 +464,119 @@ mod test {
         });
     }
 
+    #[test]
+    fn it_has_raw_bytes() {
+        let class_specific = [5u8, 0x24, 0x00, 0x10, 0x01];
+        let companion = [6u8, 0x30, 0x0F, 0x00, 0x00, 0x00];
+        let endpoint1 = endpoint_descriptor!(bEndpointAddress: 0x81, bmAttributes: 0x02, wMaxPacketSize: 0x0400, bInterval: 0, extra: companion.as_ptr(), extra_length: companion.len() as i32);
+        let endpoint2 = endpoint_descriptor!(bLength: 9, bEndpointAddress: 0x02, bmAttributes: 0x01, wMaxPacketSize: 0x00C0, bInterval: 4, bRefresh: 0, bSynchAddress: 0x83);
+        let mut setting = interface_descriptor!(endpoint1, endpoint2);
+        setting.bInterfaceClass = 0xFF;
+        setting.extra = class_specific.as_ptr();
+        setting.extra_length = class_specific.len() as i32;
+        let interface = interface!(setting);
+        let mut config = config_descriptor!(interface);
+        config.wTotalLength = 45;
+        config.bConfigurationValue = 1;
+
+        with_config!(config: config => {
+            assert_eq!(
+                config.raw(),
+                &[
+                    9, 0x02, 45, 0, 1, 1, 0, 0x00, 10,
+                    9, 0x04, 0, 0, 2, 0xFF, 0, 0, 0,
+                    5, 0x24, 0x00, 0x10, 0x01,
+                    7, 0x05, 0x81, 0x02, 0x00, 0x04, 0,
+                    6, 0x30, 0x0F, 0x00, 0x00, 0x00,
+                    9, 0x05, 0x02, 0x01, 0xC0, 0x00, 4, 0, 0x83,
+                ][..]
+            );
+            assert_eq!(config.raw().len(), config.total_length() as usize);
+        });
+    }
+
+    #[test]
+    fn it_keeps_raw_bytes_walkable_with_nonstandard_lengths() {
+        let association = [8u8, 0x0B, 0, 1, 0x0E, 0x03, 0x00, 0];
+        let endpoint = endpoint_descriptor!(bLength: 12, bEndpointAddress: 0x81);
+        let mut setting = interface_descriptor!(endpoint);
+        setting.bLength = 11;
+        let mut config = config_descriptor!(interface!(setting));
+        config.bLength = 10;
+        config.extra = association.as_ptr();
+        config.extra_length = association.len() as i32;
+
+        with_config!(config: config => {
+            let raw = config.raw();
+            assert_eq!(raw[0], 9);
+            assert_eq!(raw[17], 9);
+            assert_eq!(raw[26], 9);
+            assert_eq!(raw.len(), 35);
+            assert_eq!(config.find_descriptor(0x05), Some(&raw[26..]));
+            assert_eq!(config.interface_associations().len(), 1);
+        });
+    }
+
+    #[test]
+    fn it_has_interface_associations() {
+        let association = [8u8, 0x0B, 0, 2, 0x0E, 0x03, 0x00, 5];
+        let mut config = config_descriptor!(
+            interface!(interface_descriptor!(bInterfaceNumber: 0)),
+            interface!(interface_descriptor!(bInterfaceNumber: 1))
+        );
+        config.extra = association.as_ptr();
+        config.extra_length = association.len() as i32;
+
+        with_config!(config: config => {
+            let associations = config.interface_associations();
+
+            assert_eq!(associations.len(), 1);
+            assert_eq!(associations[0].first_interface(), 0);
+            assert_eq!(associations[0].interface_count(), 2);
+            assert_eq!(associations[0].interfaces(), 0..2);
+            assert_eq!(associations[0].function_class(), 0x0E);
+            assert_eq!(associations[0].function_sub_class(), 0x03);
+            assert_eq!(associations[0].function_protocol(), 0x00);
+            assert_eq!(associations[0].function_string_index(), Some(5));
+        });
+    }
+
+    #[test]
+    fn it_handles_missing_interface_associations() {
+        with_config!(config: config_descriptor!(interface!(interface_descriptor!(bInterfaceNumber: 0))) => {
+            assert!(config.interface_associations().is_empty());
+        });
+    }
+
+    #[test]
+    fn it_finds_class_specific_descriptors() {
+        let class_specific = [5u8, 0x24, 0x00, 0x10, 0x01, 5, 0x24, 0x01, 0x00, 0x01];
+        let mut setting = interface_descriptor!(bInterfaceNumber: 0);
+        setting.extra = class_specific.as_ptr();
+        setting.extra_length = class_specific.len() as i32;
+
+        with_config!(config: config_descriptor!(interface!(setting)) => {
+            assert_eq!(config.find_descriptor(0x24), Some(&class_specific[..5]));
+            assert_eq!(config.find_descriptor(0x04).map(<[u8]>::len), Some(9));
+            assert_eq!(config.find_descriptor(0x0B), None);
+
+            assert_eq!(config.find_cs_descriptor(0x24, 0x01), Some(&class_specific[5..]));
+            assert_eq!(config.find_cs_descriptor(0x24, 0x02), None);
+        });
+    }
+
+    #[test]
+    fn it_stops_finding_descriptors_at_invalid_length() {
+        let class_specific = [1u8, 0x24, 5, 0x24, 0x00];
+        let mut setting = interface_descriptor!(bInterfaceNumber: 0);
+        setting.extra = class_specific.as_ptr();
+        setting.extra_length = class_specific.len() as i32;
+
+        with_config!(config: config_descriptor!(interface!(setting)) => {
+            assert_eq!(config.find_descriptor(0x24), None);
+        });
+    }
+
     // Successful compilation shows that the lifetime of the endpoint descriptor(s) is the same
     // as the lifetime of the config descriptor.
     #[test]
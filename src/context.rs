@@ -1,15 +1,25 @@
 use libc::{c_char, c_int, c_void, timeval};
 
 use std::{
-    cmp::Ordering, ffi::CStr, mem, ptr, sync::Arc, sync::Mutex, sync::Once, sync::OnceLock,
-    time::Duration,
+    cmp::Ordering,
+    ffi::CStr,
+    mem, ptr,
+    sync::atomic::{AtomicBool, AtomicI32, Ordering as AtomicOrdering},
+    sync::Arc,
+    sync::Mutex,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
 };
 
 #[cfg(unix)]
 use std::os::unix::io::RawFd;
 
-use crate::hotplug::{Hotplug, HotplugBuilder, Registration};
-use crate::{device_handle::DeviceHandle, device_list::DeviceList, error};
+use crate::hotplug::{self, Hotplug, HotplugBuilder, Registration};
+use crate::{
+    device::Device, device_handle::DeviceHandle, device_list::DeviceList, error,
+    version::LibraryVersion,
+};
 use libusb1_sys::{constants::*, *};
 
 #[cfg(windows)]
@@ -22,26 +32,274 @@ type Seconds = ::libc::time_t;
 #[cfg(not(windows))]
 type MicroSeconds = ::libc::suseconds_t;
 
+/// Converts a timeout to the `timeval` taken by libusb's event handling functions.
+pub(crate) fn timeval_from_duration(duration: Duration) -> timeval {
+    timeval {
+        tv_sec: duration.as_secs() as Seconds,
+        tv_usec: duration.subsec_nanos() as MicroSeconds / 1000,
+    }
+}
+
+/// The default `libusb` context, shared by the whole process.
+///
+/// The context is initialized on first use and never exited, so it stays valid for the rest of
+/// the process regardless of how many devices, device lists or device handles referring to it are
+/// dropped.
+///
+/// Not available with the `no-global-context` feature, which leaves out all process-wide
+/// `libusb` state.
+#[cfg(not(feature = "no-global-context"))]
 #[derive(Copy, Clone, Eq, PartialEq, Default)]
 pub struct GlobalContext {}
 
 /// A `libusb` context.
+///
+/// Cloning a `Context` is cheap and yields another reference to the same underlying
+/// `libusb_context`. Two contexts compare equal with `==` if and only if they refer to the same
+/// `libusb_context`, so clones are equal to each other while contexts created by separate calls
+/// to [`Context::new`] never are. The global context is a distinct type and never compares
+/// equal to a `Context`. Use [`Context::ptr_eq`] to check whether two values share the same
+/// allocation.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Context {
     context: Arc<ContextInner>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug)]
 struct ContextInner {
     inner: ptr::NonNull<libusb_context>,
+    events: Option<EventLoop>,
+    /// The [`UsbContext`] that `inner` was borrowed from by [`UsbContext::to_context`], which
+    /// keeps it open and closes it itself.
+    owner: Option<Box<dyn std::any::Any + Send + Sync>>,
 }
 
+impl PartialEq for ContextInner {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl Eq for ContextInner {}
+
 impl Drop for ContextInner {
-    /// Closes the `libusb` context.
+    /// Stops the event thread, if any, and closes the `libusb` context.
     fn drop(&mut self) {
-        unsafe {
-            libusb_exit(self.inner.as_ptr());
+        if self.owner.is_some() {
+            return;
+        }
+
+        if let Some(events) = self.events.take() {
+            if events.is_current() {
+                // The last clone was dropped by a callback on the event thread, which is still
+                // inside `libusb_handle_events`. The thread closes the context once it has
+                // returned from there.
+                events.close_on_return();
+                return;
+            }
+
+            let _ = events.stop(self.inner.as_ptr());
+        }
+
+        unsafe { close_context(self.inner.as_ptr()) };
+    }
+}
+
+/// Calls `libusb_exit` on `context` and forgets its log callback.
+///
+/// # Safety
+///
+/// `context` must be a valid context that is no longer used afterwards.
+unsafe fn close_context(context: *mut libusb_context) {
+    libusb_exit(context);
+
+    // `libusb` may log while exiting, so the callback is only dropped afterwards. A context
+    // created later can get the same pointer and must not inherit it.
+    if let Some(log_callback_map) = LOG_CALLBACK_MAP.get() {
+        if let Ok(mut locked_table) = log_callback_map.lock() {
+            locked_table.map.remove(&context);
+        }
+    }
+}
+
+/// A thread handling the events of a context, shared by [`Context::with_event_thread`] and
+/// [`EventThreadGuard`].
+///
+/// For [`Context::with_event_thread`], the thread doesn't hold a reference to the context, so the
+/// context is dropped with its last clone, which then stops the thread. An [`EventThreadGuard`]
+/// instead lets the thread own a clone, which keeps the context open until the thread exits.
+#[derive(Debug)]
+struct EventLoop {
+    state: Arc<EventLoopState>,
+    thread: thread::JoinHandle<crate::Result<()>>,
+}
+
+/// Flags shared between an event thread and the code stopping it.
+#[derive(Debug, Default)]
+struct EventLoopState {
+    stop: AtomicBool,
+    /// Set when the context was dropped on the event thread itself, which then closes it after
+    /// leaving `libusb_handle_events`.
+    close: AtomicBool,
+}
+
+/// A context pointer moved to an event thread, which the `ContextInner` owning it outlives.
+struct RawContext(*mut libusb_context);
+
+unsafe impl Send for RawContext {}
+
+impl EventLoop {
+    fn spawn(context: ptr::NonNull<libusb_context>, owner: Option<Context>) -> crate::Result<Self> {
+        let state = Arc::new(EventLoopState::default());
+
+        let thread = {
+            let context = RawContext(context.as_ptr());
+            let state = state.clone();
+
+            thread::Builder::new()
+                .name("rusb-events".into())
+                .spawn(move || {
+                    let RawContext(context) = context;
+
+                    let mut result = Ok(());
+                    while result.is_ok() && !state.stop.load(AtomicOrdering::SeqCst) {
+                        result = handle_events(context, None);
+                        #[cfg(test)]
+                        run_event_thread_hook(context);
+                    }
+
+                    if state.close.load(AtomicOrdering::SeqCst) {
+                        unsafe { close_context(context) };
+                    }
+                    drop(owner);
+                    result
+                })
+                .map_err(|_| crate::Error::Other)?
+        };
+
+        Ok(EventLoop { state, thread })
+    }
+
+    /// Returns `true` if called from the event thread, e.g. by a callback.
+    fn is_current(&self) -> bool {
+        self.thread.thread().id() == thread::current().id()
+    }
+
+    /// Lets the event thread exit and close `context` once the current callback has returned.
+    fn close_on_return(self) {
+        self.state.close.store(true, AtomicOrdering::SeqCst);
+        self.state.stop.store(true, AtomicOrdering::SeqCst);
+    }
+
+    /// Stops the event thread and waits for it to exit.
+    ///
+    /// Returns the error that made the thread stop early, if any.
+    fn stop(self, context: *mut libusb_context) -> crate::Result<()> {
+        self.state.stop.store(true, AtomicOrdering::SeqCst);
+        // A callback on the event thread can't join it; the thread exits once it has returned.
+        if self.is_current() {
+            return Ok(());
+        }
+
+        // libusb remembers an interruption that arrives before the thread handles events
+        // again, so it can't be missed.
+        unsafe { libusb_interrupt_event_handler(context) };
+
+        self.thread.join().unwrap_or(Err(crate::Error::Other))
+    }
+}
+
+/// Functions run once by the event thread of a context after it has handled events, so that
+/// tests can act on the event thread without a device plugged in.
+#[cfg(test)]
+type EventThreadHook = (usize, Box<dyn FnOnce() + Send>);
+
+#[cfg(test)]
+static EVENT_THREAD_HOOKS: Mutex<Vec<EventThreadHook>> = Mutex::new(Vec::new());
+
+/// Makes the event thread of `context` call `hook` the next time it has handled events.
+#[cfg(test)]
+fn set_event_thread_hook(context: *mut libusb_context, hook: Box<dyn FnOnce() + Send>) {
+    EVENT_THREAD_HOOKS
+        .lock()
+        .unwrap()
+        .push((context as usize, hook));
+}
+
+#[cfg(test)]
+fn run_event_thread_hook(context: *mut libusb_context) {
+    let hook = {
+        let mut hooks = EVENT_THREAD_HOOKS.lock().unwrap();
+        match hooks.iter().position(|&(raw, _)| raw == context as usize) {
+            Some(index) => hooks.remove(index).1,
+            None => return,
+        }
+    };
+    hook();
+}
+
+/// Returns whether any file descriptor `libusb` watches for `context` is ready, or `None` if
+/// `libusb` doesn't expose them or they can't be polled.
+#[cfg(unix)]
+fn pollfds_ready(context: *mut libusb_context) -> Option<bool> {
+    let list = unsafe { libusb_get_pollfds(context) };
+    if list.is_null() {
+        return None;
+    }
+
+    let mut fds = Vec::new();
+    unsafe {
+        let mut entry = list;
+        while !(*entry).is_null() {
+            fds.push(libc::pollfd {
+                fd: (**entry).fd,
+                events: (**entry).events,
+                revents: 0,
+            });
+            entry = entry.add(1);
+        }
+        libusb_free_pollfds(list);
+    }
+
+    match unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, 0) } {
+        n if n < 0 => None,
+        n => Some(n > 0),
+    }
+}
+
+/// Handles the events of `context`, as [`UsbContext::handle_events`] does.
+fn handle_events(context: *mut libusb_context, timeout: Option<Duration>) -> crate::Result<()> {
+    handle_events_completed(context, ptr::null_mut(), timeout)
+}
+
+/// Handles the events of `context`, returning early once `*completed` is nonzero if
+/// `completed` isn't null.
+///
+/// The wait is cut short when a hotplug callback is due for
+/// [`events_settled`](crate::Hotplug::events_settled) before `timeout`.
+pub(crate) fn handle_events_completed(
+    context: *mut libusb_context,
+    completed: *mut c_int,
+    timeout: Option<Duration>,
+) -> crate::Result<()> {
+    let timeout = match (timeout, hotplug::settle_timeout(context)) {
+        (Some(timeout), Some(settle)) => Some(timeout.min(settle)),
+        (timeout, settle) => timeout.or(settle),
+    };
+    let n = unsafe {
+        match timeout {
+            Some(t) => {
+                let tv = timeval_from_duration(t);
+                libusb_handle_events_timeout_completed(context, &tv, completed)
+            }
+            None => libusb_handle_events_completed(context, completed),
         }
+    };
+    hotplug::settle_events(context);
+    if n < 0 && n != LIBUSB_ERROR_INTERRUPTED {
+        Err(error::from_libusb(n as c_int))
+    } else {
+        Ok(())
     }
 }
 
@@ -89,11 +347,122 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
     /// Get the raw libusb_context pointer, for advanced use in unsafe code.
     fn as_raw(&self) -> *mut libusb_context;
 
+    /// Returns a [`Context`] for the same `libusb` context.
+    ///
+    /// This is for code that is generic over `UsbContext` but needs a concrete [`Context`],
+    /// e.g. for [`ContextHotplug`](crate::ContextHotplug). A `Context` returns a clone of
+    /// itself, and a [`GlobalContext`] returns [`Context::global`], which refers to the same
+    /// global `libusb` context.
+    ///
+    /// Other implementations get a `Context` that holds a clone of `self`, which keeps the
+    /// `libusb` context open, and is never exited by rusb; closing it remains up to `self`.
+    fn to_context(&self) -> Context
+    where
+        Self: 'static,
+    {
+        Context {
+            context: Arc::new(ContextInner {
+                inner: ptr::NonNull::new(self.as_raw()).expect("the context is initialized"),
+                events: None,
+                owner: Some(Box::new(self.clone())),
+            }),
+        }
+    }
+
     /// Returns a list of the current USB devices.
+    ///
+    /// Every call asks `libusb` for a new list; nothing is cached by rusb. How current the list
+    /// is depends on the backend. On Linux, `libusb` keeps its device set up to date from
+    /// hotplug notifications and processes pending ones before building the list, so a
+    /// device appears once the kernel announced it. The Windows backend enumerates the devices
+    /// again on every call, and on macOS the set follows IOKit notifications. `libusb` has no
+    /// function to force a rescan beyond this, so there is none here either.
+    ///
+    /// If device discovery is disabled, e.g. on Android with
+    /// [`disable_device_discovery`](crate::disable_device_discovery), `libusb` doesn't
+    /// enumerate devices at all, so they have to be opened from file descriptors with
+    /// [`open_device_with_fd`](#method.open_device_with_fd) instead.
     fn devices(&self) -> crate::Result<DeviceList<Self>> {
         DeviceList::new_with_context(self.clone())
     }
 
+    /// Replaces the contents of `devices` with the current USB devices.
+    ///
+    /// This is equivalent to collecting [`devices`](#method.devices) into a `Vec`, but reuses
+    /// the allocation of `devices`, which is useful for loops that poll for devices. The
+    /// previous contents are released before the new list is read. On error `devices` is left
+    /// untouched.
+    fn devices_into(&self, devices: &mut Vec<Device<Self>>) -> crate::Result<()> {
+        let list = self.devices()?;
+
+        devices.clear();
+        devices.extend(list.iter());
+
+        Ok(())
+    }
+
+    /// Returns the current USB devices with their device descriptors already read.
+    ///
+    /// Each device's descriptor is read while the list is built and cached in the `Device`, so
+    /// later calls to [`device_descriptor`](Device::device_descriptor) return it without
+    /// reading it again. Devices whose descriptor can't be read are still included; they try
+    /// again when asked for the descriptor.
+    fn devices_cached(&self) -> crate::Result<Vec<Device<Self>>> {
+        let list = self.devices()?;
+
+        Ok(list
+            .iter()
+            .inspect(|device| {
+                let _ = device.device_descriptor();
+            })
+            .collect())
+    }
+
+    /// Returns the current USB devices that belong to the class `class`.
+    ///
+    /// A device matches if its device descriptor has the class code `class`. Composite devices,
+    /// whose device class is 0 or the miscellaneous class `0xEF`, match if any interface of
+    /// their active configuration has the class code `class`. Devices whose descriptors can't
+    /// be read are skipped.
+    ///
+    /// Only composite devices need their configuration descriptor read, so this is cheaper than
+    /// reading the configuration of every device.
+    fn devices_of_class(&self, class: u8) -> crate::Result<Vec<Device<Self>>> {
+        let list = self.devices()?;
+
+        Ok(list
+            .iter_with_descriptors()
+            .filter(|(device, descriptor)| match descriptor.class_code() {
+                code if code == class => true,
+                LIBUSB_CLASS_PER_INTERFACE | LIBUSB_CLASS_MISCELLANEOUS => device
+                    .active_config_descriptor()
+                    .map(|config| {
+                        config
+                            .interfaces()
+                            .flat_map(|interface| interface.descriptors())
+                            .any(|interface| interface.class_code() == class)
+                    })
+                    .unwrap_or(false),
+                _ => false,
+            })
+            .map(|(device, _)| device)
+            .collect())
+    }
+
+    /// Returns the devices of the context that are not hubs.
+    ///
+    /// Hubs, including the root hubs of the host controllers, are the devices with the class
+    /// code `LIBUSB_CLASS_HUB` (0x09). Devices whose descriptors can't be read are skipped.
+    fn devices_excluding_hubs(&self) -> crate::Result<Vec<Device<Self>>> {
+        let list = self.devices()?;
+
+        Ok(list
+            .iter_with_descriptors()
+            .filter(|(_, descriptor)| descriptor.class_code() != LIBUSB_CLASS_HUB)
+            .map(|(device, _)| device)
+            .collect())
+    }
+
     /// Convenience function to open a device by its vendor ID and product ID.
     ///
     /// This function is provided as a convenience for building prototypes without having to
@@ -113,6 +482,28 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
         Some(unsafe { DeviceHandle::from_libusb(self.clone(), ptr) })
     }
 
+    /// Opens the first device matching `vendor_id` and `product_id`.
+    ///
+    /// Unlike [`open_device_with_vid_pid`](#method.open_device_with_vid_pid), this tells apart
+    /// a missing device from one that can't be opened. It returns `Error::NotFound` if no
+    /// matching device is connected, and the error from [`Device::open`] if the device is found
+    /// but opening it fails, for example `Error::Access` for insufficient permissions or
+    /// `Error::NotSupported` if no suitable driver is bound on Windows.
+    fn find_and_open(&self, vendor_id: u16, product_id: u16) -> crate::Result<DeviceHandle<Self>> {
+        for device in self.devices()?.iter() {
+            let descriptor = match device.device_descriptor() {
+                Ok(descriptor) => descriptor,
+                Err(_) => continue,
+            };
+
+            if descriptor.vendor_id() == vendor_id && descriptor.product_id() == product_id {
+                return device.open();
+            }
+        }
+
+        Err(crate::Error::NotFound)
+    }
+
     /// Opens the device with a pre-opened file descriptor.
     ///
     /// This is UNIX-only and platform-specific. It is currently working with
@@ -137,6 +528,14 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
         }
     }
 
+    /// Returns the version of the `libusb` library backing this context.
+    ///
+    /// This is the same as [`version`](crate::version); the library is linked once per process,
+    /// so every context reports the same version.
+    fn library_version(&self) -> LibraryVersion {
+        crate::version()
+    }
+
     /// Sets the log level of a `libusb` for context.
     fn set_log_level(&mut self, level: LogLevel) {
         unsafe {
@@ -204,24 +603,145 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
     /// If timeout less then 1 microseconds then this function will handle any already-pending
     /// events and then immediately return in non-blocking style.
     /// If timeout is [None] then function will handle any pending events in blocking mode.
+    ///
+    /// To only handle events that are already pending, prefer [poll_events][`Self::poll_events()`].
+    ///
+    /// A wakeup by [`interrupt_handle_events`](#method.interrupt_handle_events) is not an error:
+    /// this returns `Ok(())` as after any other event, so an event loop only has to check its
+    /// own stop condition after each call.
+    ///
+    /// While a hotplug callback waits for
+    /// [`events_settled`](crate::Hotplug::events_settled), the wait is limited to when that
+    /// is due, and the callback is settled before returning.
     fn handle_events(&self, timeout: Option<Duration>) -> crate::Result<()> {
-        let n = unsafe {
-            match timeout {
-                Some(t) => {
-                    let tv = timeval {
-                        tv_sec: t.as_secs() as Seconds,
-                        tv_usec: t.subsec_nanos() as MicroSeconds / 1000,
-                    };
-                    libusb_handle_events_timeout_completed(self.as_raw(), &tv, ptr::null_mut())
-                }
-                None => libusb_handle_events_completed(self.as_raw(), ptr::null_mut()),
-            }
-        };
-        if n < 0 {
-            Err(error::from_libusb(n as c_int))
-        } else {
-            Ok(())
+        handle_events(self.as_raw(), timeout)
+    }
+
+    /// Handles events until `completed` becomes nonzero or `timeout` elapses.
+    ///
+    /// This is the way to wait for a single asynchronous transfer: its callback sets
+    /// `completed`, and this returns `Ok(true)` once it has. `Ok(false)` means the timeout
+    /// elapsed first. With a `timeout` of [None] it waits until `completed` is set.
+    ///
+    /// `libusb` checks `completed` while holding the event handling lock, so a flag set from a
+    /// transfer callback is never missed, even if several threads handle events at the same time.
+    /// Unlike calling [`handle_events`](#method.handle_events) in a loop, this neither spins nor
+    /// sleeps through the completion.
+    ///
+    /// ```no_run
+    /// use rusb::ffi::{self, libusb_transfer};
+    /// use rusb::{Context, UsbContext};
+    /// use std::sync::atomic::{AtomicI32, Ordering};
+    /// use std::time::Duration;
+    ///
+    /// extern "system" fn callback(transfer: *mut libusb_transfer) {
+    ///     let completed = unsafe { &*((*transfer).user_data as *const AtomicI32) };
+    ///     completed.store(1, Ordering::SeqCst);
+    /// }
+    ///
+    /// # fn main() -> rusb::Result<()> {
+    /// let context = Context::new()?;
+    /// let handle = context.open_device_with_vid_pid(0x1234, 0x5678).unwrap();
+    /// let completed = AtomicI32::new(0);
+    /// let mut buf = [0u8; 64];
+    ///
+    /// unsafe {
+    ///     let transfer = ffi::libusb_alloc_transfer(0);
+    ///     ffi::libusb_fill_bulk_transfer(
+    ///         transfer,
+    ///         handle.as_raw(),
+    ///         0x81,
+    ///         buf.as_mut_ptr(),
+    ///         buf.len() as i32,
+    ///         callback,
+    ///         &completed as *const AtomicI32 as *mut _,
+    ///         0,
+    ///     );
+    ///     ffi::libusb_submit_transfer(transfer);
+    ///
+    ///     if !context.handle_events_for(&completed, Some(Duration::from_secs(1)))? {
+    ///         // Timed out: cancel and wait for the callback before freeing the transfer.
+    ///         ffi::libusb_cancel_transfer(transfer);
+    ///         context.handle_events_for(&completed, None)?;
+    ///     }
+    ///     ffi::libusb_free_transfer(transfer);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "libusb_handle_events_timeout_completed")]
+    fn handle_events_for(
+        &self,
+        completed: &AtomicI32,
+        timeout: Option<Duration>,
+    ) -> crate::Result<bool> {
+        let flag = completed as *const AtomicI32 as *mut c_int;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        while completed.load(AtomicOrdering::SeqCst) == 0 {
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Some(remaining),
+                    _ => return Ok(false),
+                },
+                None => None,
+            };
+            handle_events_completed(self.as_raw(), flag, remaining)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Waits for the next event and handles it, unless `completed` is already nonzero.
+    ///
+    /// This is one step of an event loop for schedulers that drive `libusb` themselves. Besides
+    /// transfer completions, `libusb` has timers of its own, such as transfer timeouts, that
+    /// some platforms only process when events are handled once they expire. The wait is
+    /// therefore limited to [`next_timeout`](#method.next_timeout): this returns when an event
+    /// was handled, when `libusb`'s next timer expired and was processed, or when `completed`
+    /// was set by a callback, whichever comes first. Without pending timers it blocks until an
+    /// event arrives.
+    ///
+    /// Call it in a loop and check `completed` or any other condition after each call. Use
+    /// [`handle_events_for`](#method.handle_events_for) to simply wait for one transfer.
+    fn wait_for_event(&self, completed: &AtomicI32) -> crate::Result<()> {
+        if completed.load(AtomicOrdering::SeqCst) != 0 {
+            return Ok(());
+        }
+
+        let timeout = self.next_timeout()?;
+        let flag = completed as *const AtomicI32 as *mut c_int;
+        handle_events_completed(self.as_raw(), flag, timeout)
+    }
+
+    /// Returns true if events are ready to be handled right now.
+    ///
+    /// This is the case when a file descriptor `libusb` watches is readable, e.g. because a
+    /// transfer completed or a device was connected, or when one of `libusb`'s timers has
+    /// expired. An event loop can skip [`handle_events`](#method.handle_events) while this is
+    /// false, since handling events would find nothing to do. Transfers that are still in flight
+    /// don't count until they complete.
+    ///
+    /// `libusb` has no direct query for this, so it is derived from its file descriptors. Where
+    /// they aren't available, as on Windows, this conservatively returns true.
+    #[doc(alias = "libusb_get_pollfds")]
+    fn has_pending_events(&self) -> bool {
+        if let Ok(Some(Duration::ZERO)) = self.next_timeout() {
+            return true;
         }
+
+        #[cfg(unix)]
+        return pollfds_ready(self.as_raw()).unwrap_or(true);
+
+        #[cfg(not(unix))]
+        return true;
+    }
+
+    /// Handle any already-pending events and return immediately, without blocking.
+    ///
+    /// This is equivalent to `handle_events(Some(Duration::ZERO))`.
+    fn poll_events(&self) -> crate::Result<()> {
+        self.handle_events(Some(Duration::ZERO))
     }
 
     /// Interrupt any active thread that is handling events (for example with
@@ -231,6 +751,10 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
         unsafe { libusb_interrupt_event_handler(self.as_raw()) }
     }
 
+    /// Returns how long until `libusb`'s next timer expires, or a hotplug callback is due for
+    /// [`events_settled`](crate::Hotplug::events_settled), whichever comes first. [None] means
+    /// neither is pending.
+    #[doc(alias = "libusb_get_next_timeout")]
     fn next_timeout(&self) -> crate::Result<Option<Duration>> {
         let mut tv = timeval {
             tv_sec: 0,
@@ -238,13 +762,15 @@ pub trait UsbContext: Clone + Sized + Send + Sync {
         };
         let n = unsafe { libusb_get_next_timeout(self.as_raw(), &mut tv) };
 
-        match n.cmp(&0) {
-            Ordering::Less => Err(error::from_libusb(n as c_int)),
-            Ordering::Equal => Ok(None),
-            Ordering::Greater => {
-                let duration = Duration::new(tv.tv_sec as _, (tv.tv_usec * 1000) as _);
-                Ok(Some(duration))
-            }
+        let timeout = match n.cmp(&0) {
+            Ordering::Less => return Err(error::from_libusb(n as c_int)),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(Duration::new(tv.tv_sec as _, (tv.tv_usec * 1000) as _)),
+        };
+
+        match (timeout, hotplug::settle_timeout(self.as_raw())) {
+            (Some(timeout), Some(settle)) => Ok(Some(timeout.min(settle))),
+            (timeout, settle) => Ok(timeout.or(settle)),
         }
     }
 }
@@ -253,12 +779,17 @@ impl UsbContext for Context {
     fn as_raw(&self) -> *mut libusb_context {
         self.context.inner.as_ptr()
     }
+
+    fn to_context(&self) -> Context {
+        self.clone()
+    }
 }
 
+#[cfg(not(feature = "no-global-context"))]
 impl UsbContext for GlobalContext {
     fn as_raw(&self) -> *mut libusb_context {
         static mut USB_CONTEXT: *mut libusb_context = ptr::null_mut();
-        static ONCE: Once = Once::new();
+        static ONCE: std::sync::Once = std::sync::Once::new();
 
         ONCE.call_once(|| {
             let mut context = mem::MaybeUninit::<*mut libusb_context>::uninit();
@@ -275,10 +806,29 @@ impl UsbContext for GlobalContext {
         // Clone data that is safe to use concurrently.
         unsafe { USB_CONTEXT }
     }
+
+    fn to_context(&self) -> Context {
+        Context::global()
+    }
 }
 
 impl Context {
     /// Opens a new `libusb` context.
+    ///
+    /// ## Errors
+    ///
+    /// The error reported by `libusb_init` is returned as is, so the cause of a failure can be
+    /// told apart. For example, `Access` usually means that the process may not access the USB
+    /// device nodes, as happens in containers without `usbfs`, and `NoMem` that an allocation
+    /// failed.
+    ///
+    /// ```
+    /// match rusb::Context::new() {
+    ///     Ok(context) => println!("libusb initialized: {:?}", context),
+    ///     Err(rusb::Error::Access) => eprintln!("no permission to access USB devices"),
+    ///     Err(e) => eprintln!("can't initialize libusb: {}", e),
+    /// }
+    /// ```
     pub fn new() -> crate::Result<Self> {
         let mut context = mem::MaybeUninit::<*mut libusb_context>::uninit();
 
@@ -288,6 +838,23 @@ impl Context {
     }
 
     /// Creates a new `libusb` context and sets runtime options.
+    ///
+    /// The options are applied in order, right after `libusb_init` and before anything else is
+    /// done with the context, which is when `libusb` expects them:
+    ///
+    /// * `UsbOption::use_usbdk`, available on Windows only, selects the UsbDk backend instead
+    ///   of WinUSB. It has to be set before the first device is listed or
+    ///   opened, which this guarantees.
+    /// * [`UsbOption::weak_authority`](crate::UsbOption::weak_authority) is read while
+    ///   `libusb` initializes. This function can't apply it any earlier than right after
+    ///   `libusb_init`, as `libusb1-sys` doesn't bind `libusb_init_context`, which would take
+    ///   the options of the context being created. The initial device scan has therefore
+    ///   already happened, and the option doesn't prevent it. Set it process-wide with
+    ///   [`disable_device_discovery`](crate::disable_device_discovery) before creating the
+    ///   context instead.
+    ///
+    /// `libusb` offers no other backend selection. If an option fails, the error is returned
+    /// and the context is closed again.
     pub fn with_options(opts: &[crate::UsbOption]) -> crate::Result<Self> {
         let mut this = Self::new()?;
 
@@ -298,6 +865,129 @@ impl Context {
         Ok(this)
     }
 
+    /// Opens a new `libusb` context together with a thread that handles its events.
+    ///
+    /// Asynchronous transfers and hotplug callbacks only make progress while some thread handles
+    /// events. The returned [`EventThreadGuard`] owns such a thread, which calls
+    /// [`handle_events`](UsbContext::handle_events) until the guard is dropped or
+    /// [stopped](EventThreadGuard::stop).
+    ///
+    /// Prefer [`with_event_thread`](#method.with_event_thread), which ties the thread to the
+    /// context. Use this instead when events should stop being handled at a definite point while
+    /// the context stays open, or to get the error that made the thread stop early.
+    pub fn new_with_event_thread() -> crate::Result<(Self, EventThreadGuard)> {
+        let context = Self::new()?;
+        let guard = EventThreadGuard::spawn(context.clone())?;
+
+        Ok((context, guard))
+    }
+
+    /// Opens a new `libusb` context whose clones share one thread handling its events.
+    ///
+    /// Unlike [`new_with_event_thread`](#method.new_with_event_thread), the thread belongs to
+    /// the context itself: every clone uses the same thread, and it is stopped and joined when
+    /// the last clone is dropped, right before the context is closed. Clones can be moved to
+    /// worker threads, which perform transfers without handling events themselves.
+    ///
+    /// The thread stops early if event handling fails. The last clone may also be dropped by a
+    /// callback running on the event thread, such as a hotplug callback; the thread then closes
+    /// the context itself once the callback has returned.
+    ///
+    /// This is the recommended way to get a context whose events are handled in the background.
+    /// Both constructors run the same event thread; see
+    /// [`new_with_event_thread`](#method.new_with_event_thread) for when to manage it separately.
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use rusb::{Context, UsbContext};
+    ///
+    /// let context = Context::with_event_thread()?;
+    ///
+    /// let worker = {
+    ///     let context = context.clone();
+    ///     thread::spawn(move || -> rusb::Result<()> {
+    ///         let handle = context.find_and_open(0x1234, 0x5678)?;
+    ///         // ... transfers on `handle` ...
+    ///         Ok(())
+    ///     })
+    /// };
+    ///
+    /// worker.join().unwrap()?;
+    /// # Ok::<(), rusb::Error>(())
+    /// ```
+    ///
+    /// See the `shared_context` example for a complete program.
+    pub fn with_event_thread() -> crate::Result<Self> {
+        let mut context = Self::new()?;
+
+        let inner = Arc::get_mut(&mut context.context).expect("a new context is not shared");
+        inner.events = Some(EventLoop::spawn(inner.inner, None)?);
+
+        Ok(context)
+    }
+
+    /// Returns the id of the thread created by [`with_event_thread`](#method.with_event_thread).
+    #[cfg(test)]
+    fn event_thread(&self) -> Option<thread::ThreadId> {
+        let events = self.context.events.as_ref()?;
+        Some(events.thread.thread().id())
+    }
+
+    /// Returns `true` if both contexts are clones of the same `Context`.
+    ///
+    /// Unlike `==`, which compares the underlying `libusb_context` pointers, this compares the
+    /// identity of the shared allocation that owns the context.
+    pub fn ptr_eq(&self, other: &Context) -> bool {
+        Arc::ptr_eq(&self.context, &other.context)
+    }
+
+    /// Closes the context and returns once `libusb` has been shut down.
+    ///
+    /// Dropping a `Context` only closes the `libusb` context with its last clone, which may be
+    /// held elsewhere, e.g. by a [`Device`](crate::Device), a
+    /// [`DeviceHandle`](crate::DeviceHandle) or an [`EventThreadGuard`]. This gives a definite
+    /// end instead, which keeps teardown in tests deterministic: if `self` is the last clone,
+    /// the event thread started by [`with_event_thread`](#method.with_event_thread) is stopped
+    /// and joined, `libusb_exit` is called, which waits for `libusb`'s own threads, and `Ok` is
+    /// returned after all of that has finished.
+    ///
+    /// ## Errors
+    ///
+    /// If other clones are still alive, nothing is closed and the context is handed back.
+    #[doc(alias = "libusb_exit")]
+    pub fn shutdown_blocking(self) -> Result<(), Context> {
+        match Arc::try_unwrap(self.context) {
+            Ok(inner) => {
+                drop(inner);
+                Ok(())
+            }
+            Err(context) => Err(Context { context }),
+        }
+    }
+
+    /// Returns a `Context` for the global `libusb` context used by [`GlobalContext`].
+    ///
+    /// Devices and handles opened through it share the global context with those opened through
+    /// `GlobalContext`. Like `GlobalContext`, it is never exited, however many clones are
+    /// dropped.
+    #[cfg(not(feature = "no-global-context"))]
+    pub fn global() -> Self {
+        static GLOBAL: OnceLock<Context> = OnceLock::new();
+
+        // The static keeps one reference for the rest of the process, so `ContextInner::drop`
+        // never runs and the global context is never exited.
+        GLOBAL
+            .get_or_init(|| Context {
+                context: Arc::new(ContextInner {
+                    inner: ptr::NonNull::new(GlobalContext::default().as_raw())
+                        .expect("the global context is initialized"),
+                    events: None,
+                    owner: None,
+                }),
+            })
+            .clone()
+    }
+
     /// Creates rusb Context from existing libusb context.
     /// Note: This transfers ownership of the context to Rust.
     /// # Safety
@@ -307,11 +997,55 @@ impl Context {
         Context {
             context: Arc::new(ContextInner {
                 inner: ptr::NonNull::new_unchecked(raw),
+                events: None,
+                owner: None,
             }),
         }
     }
 }
 
+/// Owns a thread that handles the events of a [`Context`].
+///
+/// Created by [`Context::new_with_event_thread`]. Dropping the guard stops the thread: it is
+/// interrupted with [`interrupt_handle_events`](UsbContext::interrupt_handle_events) and joined.
+/// The thread also stops by itself if handling events fails.
+#[derive(Debug)]
+pub struct EventThreadGuard {
+    context: Context,
+    events: Option<EventLoop>,
+}
+
+impl EventThreadGuard {
+    fn spawn(context: Context) -> crate::Result<Self> {
+        let events = EventLoop::spawn(context.context.inner, Some(context.clone()))?;
+
+        Ok(EventThreadGuard {
+            context,
+            events: Some(events),
+        })
+    }
+
+    /// Stops the event thread and waits for it to exit.
+    ///
+    /// Returns the error that made the thread stop early, if any.
+    pub fn stop(mut self) -> crate::Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> crate::Result<()> {
+        match self.events.take() {
+            Some(events) => events.stop(self.context.as_raw()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for EventThreadGuard {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
 /// Library logging levels.
 #[derive(Clone, Copy)]
 pub enum LogLevel {
@@ -371,3 +1105,195 @@ impl LogCallbackMode {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicI32, Ordering},
+            mpsc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[cfg(not(feature = "no-global-context"))]
+    use super::GlobalContext;
+    use super::{set_event_thread_hook, Context, LogCallbackMode, UsbContext, LOG_CALLBACK_MAP};
+    use crate::Error;
+
+    #[test]
+    fn it_shares_the_event_thread_between_clones() {
+        // Several threads perform control transfers through clones of one context, whose
+        // completions are all delivered by the shared event thread.
+        let context = context_or_skip!(Context::with_event_thread());
+        let event_thread = context.event_thread();
+        assert!(event_thread.is_some());
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let context = context.clone();
+                thread::spawn(move || {
+                    // Devices this process may not open are skipped; the others are sent a
+                    // GET_STATUS request, whose completion the event thread has to deliver.
+                    let results: Vec<_> = context
+                        .devices()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|device| device.open().ok())
+                        .map(|handle| handle.device_status(Duration::from_secs(1)).map(|_| ()))
+                        .collect();
+                    (context.event_thread(), results)
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            let (thread, results) = worker.join().unwrap();
+            assert_eq!(thread, event_thread);
+
+            // A lost completion would show up as `Timeout`. Devices may still refuse the request.
+            for result in results {
+                assert!(
+                    matches!(
+                        result,
+                        Ok(())
+                            | Err(Error::Pipe)
+                            | Err(Error::Access)
+                            | Err(Error::NotSupported)
+                            | Err(Error::NoDevice)
+                    ),
+                    "unexpected transfer result {:?}",
+                    result
+                );
+            }
+        }
+        assert_eq!(
+            Context::new().map(|context| context.event_thread()),
+            Ok(None)
+        );
+
+        // Dropping the last clone joins the event thread; this would hang if it kept running.
+        drop(context);
+    }
+
+    #[test]
+    fn it_closes_a_context_dropped_on_its_event_thread() {
+        let mut context = context_or_skip!(Context::with_event_thread());
+        let raw = context.as_raw();
+
+        // The log callback is forgotten when the context is closed, which drops `closed` and so
+        // disconnects the channel, whether or not `libusb` ever logged anything.
+        let (closed, on_close) = mpsc::channel::<()>();
+        context.set_log_callback(
+            Box::new(move |_, _| {
+                let _ = &closed;
+            }),
+            LogCallbackMode::Context,
+        );
+
+        // The hook drops the last clone on the event thread, once this thread has dropped its own.
+        let (release, on_release) = mpsc::channel::<()>();
+        let last_clone = context.clone();
+        set_event_thread_hook(
+            raw,
+            Box::new(move || {
+                on_release.recv().unwrap();
+                drop(last_clone);
+            }),
+        );
+        context.interrupt_handle_events();
+        drop(context);
+        release.send(()).unwrap();
+
+        assert_eq!(
+            on_close.recv_timeout(Duration::from_secs(5)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
+        let map = LOG_CALLBACK_MAP.get().unwrap().lock().unwrap();
+        assert!(!map.map.contains_key(&raw));
+    }
+
+    #[cfg(not(feature = "no-global-context"))]
+    #[test]
+    fn it_converts_to_a_context_for_the_same_libusb_context() {
+        let context = context_or_skip!(Context::new());
+        assert!(context.to_context().ptr_eq(&context));
+
+        let global = GlobalContext::default();
+        assert_eq!(global.to_context().as_raw(), global.as_raw());
+        assert!(global.to_context().ptr_eq(&Context::global()));
+    }
+
+    #[test]
+    fn it_converts_other_contexts_without_closing_them() {
+        #[derive(Clone)]
+        struct Wrapper(Context);
+
+        impl UsbContext for Wrapper {
+            fn as_raw(&self) -> *mut libusb1_sys::libusb_context {
+                self.0.as_raw()
+            }
+        }
+
+        let wrapper = Wrapper(context_or_skip!(Context::new()));
+
+        let context = wrapper.to_context();
+        assert_eq!(context.as_raw(), wrapper.as_raw());
+        assert!(!context.ptr_eq(&wrapper.0));
+
+        // The converted context keeps the wrapper, and so the `libusb` context, alive.
+        let raw = wrapper.as_raw();
+        drop(wrapper);
+        assert_eq!(context.as_raw(), raw);
+        assert!(context.devices().is_ok());
+    }
+
+    #[test]
+    fn it_shuts_down_with_the_last_clone() {
+        let context = context_or_skip!(Context::with_event_thread());
+        let clone = context.clone();
+
+        let context = context.shutdown_blocking().unwrap_err();
+        assert!(context.ptr_eq(&clone));
+        drop(clone);
+        assert!(context.shutdown_blocking().is_ok());
+    }
+
+    #[test]
+    fn it_forgets_the_log_callback_of_a_closed_context() {
+        for _ in 0..16 {
+            let mut context = context_or_skip!(Context::new());
+            context.set_log_callback(Box::new(|_, _| {}), LogCallbackMode::Context);
+            let raw = context.as_raw();
+            drop(context);
+
+            let map = LOG_CALLBACK_MAP.get().unwrap().lock().unwrap();
+            assert!(!map.map.contains_key(&raw));
+        }
+    }
+
+    #[test]
+    fn it_handles_events_until_completed_or_timeout() {
+        let context = context_or_skip!(Context::new());
+
+        let completed = AtomicI32::new(0);
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(context.handle_events_for(&completed, timeout), Ok(false));
+
+        completed.store(1, Ordering::SeqCst);
+        assert_eq!(context.handle_events_for(&completed, timeout), Ok(true));
+        assert_eq!(context.handle_events_for(&completed, None), Ok(true));
+        assert_eq!(context.wait_for_event(&completed), Ok(()));
+    }
+
+    #[test]
+    fn it_has_no_pending_events_once_handled() {
+        let context = context_or_skip!(Context::new());
+
+        assert_eq!(context.poll_events(), Ok(()));
+        if cfg!(unix) {
+            assert!(!context.has_pending_events());
+        }
+    }
+}
@@ -0,0 +1,621 @@
+use std::ptr;
+
+use libc::c_int;
+use libusb1_sys::{
+    constants::*, libusb_config_descriptor, libusb_device_descriptor, libusb_endpoint_descriptor,
+    libusb_interface, libusb_interface_descriptor,
+};
+
+use crate::{
+    config_descriptor::{self, ConfigDescriptor},
+    device_descriptor::{self, DeviceDescriptor},
+    fields::{TransferType, Version},
+};
+
+/// Builds a [`DeviceDescriptor`] without a device, e.g. to test code that handles descriptors.
+///
+/// Unset fields describe a USB 2.0 device of version 1.0 with one configuration, a 64 byte
+/// control endpoint, no class and no strings.
+///
+/// ```
+/// use rusb::{DeviceDescriptorBuilder, Version};
+///
+/// let descriptor = DeviceDescriptorBuilder::new(0x1234, 0x5678)
+///     .usb_version(Version(3, 2, 0))
+///     .class_code(0xFF)
+///     .build();
+///
+/// assert_eq!(descriptor.vendor_id(), 0x1234);
+/// assert_eq!(descriptor.usb_version(), Version(3, 2, 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeviceDescriptorBuilder {
+    usb_version: Version,
+    class_code: u8,
+    sub_class_code: u8,
+    protocol_code: u8,
+    max_packet_size: u8,
+    vendor_id: u16,
+    product_id: u16,
+    device_version: Version,
+    manufacturer_string_index: u8,
+    product_string_index: u8,
+    serial_number_string_index: u8,
+    num_configurations: u8,
+}
+
+impl DeviceDescriptorBuilder {
+    /// Returns a builder for a device with the given vendor and product IDs.
+    pub fn new(vendor_id: u16, product_id: u16) -> Self {
+        DeviceDescriptorBuilder {
+            usb_version: Version(2, 0, 0),
+            class_code: 0,
+            sub_class_code: 0,
+            protocol_code: 0,
+            max_packet_size: 64,
+            vendor_id,
+            product_id,
+            device_version: Version(1, 0, 0),
+            manufacturer_string_index: 0,
+            product_string_index: 0,
+            serial_number_string_index: 0,
+            num_configurations: 1,
+        }
+    }
+
+    /// Sets the USB version, `bcdUSB`.
+    pub fn usb_version(&mut self, version: Version) -> &mut Self {
+        self.usb_version = version;
+        self
+    }
+
+    /// Sets the device class code, `bDeviceClass`.
+    pub fn class_code(&mut self, class_code: u8) -> &mut Self {
+        self.class_code = class_code;
+        self
+    }
+
+    /// Sets the device sub class code, `bDeviceSubClass`.
+    pub fn sub_class_code(&mut self, sub_class_code: u8) -> &mut Self {
+        self.sub_class_code = sub_class_code;
+        self
+    }
+
+    /// Sets the device protocol code, `bDeviceProtocol`.
+    pub fn protocol_code(&mut self, protocol_code: u8) -> &mut Self {
+        self.protocol_code = protocol_code;
+        self
+    }
+
+    /// Sets the raw `bMaxPacketSize0` field.
+    pub fn max_packet_size(&mut self, max_packet_size: u8) -> &mut Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Sets the device version, `bcdDevice`.
+    pub fn device_version(&mut self, version: Version) -> &mut Self {
+        self.device_version = version;
+        self
+    }
+
+    /// Sets the index of the manufacturer string descriptor, 0 for none.
+    pub fn manufacturer_string_index(&mut self, index: u8) -> &mut Self {
+        self.manufacturer_string_index = index;
+        self
+    }
+
+    /// Sets the index of the product string descriptor, 0 for none.
+    pub fn product_string_index(&mut self, index: u8) -> &mut Self {
+        self.product_string_index = index;
+        self
+    }
+
+    /// Sets the index of the serial number string descriptor, 0 for none.
+    pub fn serial_number_string_index(&mut self, index: u8) -> &mut Self {
+        self.serial_number_string_index = index;
+        self
+    }
+
+    /// Sets the number of configurations.
+    pub fn num_configurations(&mut self, num_configurations: u8) -> &mut Self {
+        self.num_configurations = num_configurations;
+        self
+    }
+
+    /// Returns the device descriptor.
+    pub fn build(&self) -> DeviceDescriptor {
+        device_descriptor::from_libusb(libusb_device_descriptor {
+            bLength: LIBUSB_DT_DEVICE_SIZE,
+            bDescriptorType: LIBUSB_DT_DEVICE,
+            bcdUSB: to_bcd(self.usb_version),
+            bDeviceClass: self.class_code,
+            bDeviceSubClass: self.sub_class_code,
+            bDeviceProtocol: self.protocol_code,
+            bMaxPacketSize0: self.max_packet_size,
+            idVendor: self.vendor_id,
+            idProduct: self.product_id,
+            bcdDevice: to_bcd(self.device_version),
+            iManufacturer: self.manufacturer_string_index,
+            iProduct: self.product_string_index,
+            iSerialNumber: self.serial_number_string_index,
+            bNumConfigurations: self.num_configurations,
+        })
+    }
+}
+
+/// Builds a [`ConfigDescriptor`] without a device, e.g. to test code that handles descriptors.
+///
+/// Interfaces and their alternate settings are added with [`interface`](#method.interface),
+/// and their endpoints with [`InterfaceDescriptorBuilder::endpoint`]. The configuration's
+/// `wTotalLength` and `bNumInterfaces` follow from what was added. Unset fields describe
+/// configuration 1 of a bus-powered device drawing 100 mA.
+///
+/// ```
+/// use rusb::{
+///     ConfigDescriptorBuilder, Direction, EndpointDescriptorBuilder,
+///     InterfaceDescriptorBuilder, TransferType,
+/// };
+///
+/// let config = ConfigDescriptorBuilder::new()
+///     .interface(
+///         InterfaceDescriptorBuilder::new(0)
+///             .class_code(0xFF)
+///             .endpoint(&EndpointDescriptorBuilder::new(0x81))
+///             .endpoint(EndpointDescriptorBuilder::new(0x02).max_packet_size(512)),
+///     )
+///     .build();
+///
+/// let setting = config.interfaces().next().unwrap().descriptors().next().unwrap();
+/// let endpoint = setting.endpoint_descriptors().next().unwrap();
+/// assert_eq!(endpoint.direction(), Direction::In);
+/// assert_eq!(endpoint.transfer_type(), TransferType::Bulk);
+/// assert_eq!(config.raw().len(), usize::from(config.total_length()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigDescriptorBuilder {
+    number: u8,
+    description_string_index: u8,
+    attributes: u8,
+    max_power: u8,
+    extra: Vec<u8>,
+    interfaces: Vec<InterfaceDescriptorBuilder>,
+}
+
+impl ConfigDescriptorBuilder {
+    /// Returns a builder for a configuration without interfaces.
+    pub fn new() -> Self {
+        ConfigDescriptorBuilder {
+            number: 1,
+            description_string_index: 0,
+            attributes: 0x80,
+            max_power: 50,
+            extra: Vec::new(),
+            interfaces: Vec::new(),
+        }
+    }
+
+    /// Sets the configuration number, `bConfigurationValue`.
+    pub fn number(&mut self, number: u8) -> &mut Self {
+        self.number = number;
+        self
+    }
+
+    /// Sets the index of the string descriptor describing the configuration, 0 for none.
+    pub fn description_string_index(&mut self, index: u8) -> &mut Self {
+        self.description_string_index = index;
+        self
+    }
+
+    /// Sets whether the device is self-powered in this configuration.
+    pub fn self_powered(&mut self, self_powered: bool) -> &mut Self {
+        self.set_attribute(0x40, self_powered);
+        self
+    }
+
+    /// Sets whether the device supports remote wakeup in this configuration.
+    pub fn remote_wakeup(&mut self, remote_wakeup: bool) -> &mut Self {
+        self.set_attribute(0x20, remote_wakeup);
+        self
+    }
+
+    /// Sets the maximum power consumption in milliamps, in the 2 mA units of `bMaxPower`.
+    /// Values that don't fit are capped at 510 mA.
+    pub fn max_power(&mut self, milliamps: u16) -> &mut Self {
+        self.max_power = (milliamps / 2).min(255) as u8;
+        self
+    }
+
+    /// Sets the class or vendor specific descriptors following the configuration descriptor.
+    pub fn extra(&mut self, extra: &[u8]) -> &mut Self {
+        self.extra = extra.to_vec();
+        self
+    }
+
+    /// Adds an alternate setting of an interface.
+    ///
+    /// Alternate settings of the same interface number are grouped into one interface, in the
+    /// order they were added. Interfaces are ordered by their first setting.
+    pub fn interface(&mut self, setting: &InterfaceDescriptorBuilder) -> &mut Self {
+        self.interfaces.push(setting.clone());
+        self
+    }
+
+    /// Returns the configuration descriptor.
+    pub fn build(&self) -> ConfigDescriptor {
+        let mut storage = Box::new(ConfigStorage {
+            config: libusb_config_descriptor {
+                bLength: LIBUSB_DT_CONFIG_SIZE,
+                bDescriptorType: LIBUSB_DT_CONFIG,
+                wTotalLength: 0,
+                bNumInterfaces: 0,
+                bConfigurationValue: self.number,
+                iConfiguration: self.description_string_index,
+                bmAttributes: self.attributes,
+                bMaxPower: self.max_power,
+                interface: ptr::null(),
+                extra: ptr::null(),
+                extra_length: 0,
+            },
+            interfaces: Vec::new(),
+            settings: Vec::new(),
+            endpoints: Vec::new(),
+            extras: Vec::new(),
+        });
+
+        let mut numbers = Vec::new();
+        for setting in &self.interfaces {
+            if !numbers.contains(&setting.number) {
+                numbers.push(setting.number);
+            }
+        }
+
+        let mut total_length = usize::from(LIBUSB_DT_CONFIG_SIZE) + self.extra.len();
+        for number in numbers {
+            let mut settings = Vec::new();
+
+            for setting in self.interfaces.iter().filter(|s| s.number == number) {
+                let endpoints = setting
+                    .endpoints
+                    .iter()
+                    .map(|endpoint| {
+                        total_length += usize::from(LIBUSB_DT_ENDPOINT_SIZE) + endpoint.extra.len();
+                        endpoint.to_libusb(&mut storage.extras)
+                    })
+                    .collect::<Vec<_>>();
+
+                total_length += usize::from(LIBUSB_DT_INTERFACE_SIZE) + setting.extra.len();
+                settings.push(setting.to_libusb(&endpoints, &mut storage.extras));
+                // Moving the Vec keeps its buffer, so the pointer taken above stays valid.
+                storage.endpoints.push(endpoints);
+            }
+
+            storage.interfaces.push(libusb_interface {
+                altsetting: settings.as_ptr(),
+                num_altsetting: settings.len() as c_int,
+            });
+            storage.settings.push(settings);
+        }
+
+        let (extra, extra_length) = keep_extra(&self.extra, &mut storage.extras);
+        storage.config.extra = extra;
+        storage.config.extra_length = extra_length;
+        storage.config.interface = storage.interfaces.as_ptr();
+        storage.config.bNumInterfaces = storage.interfaces.len() as u8;
+        storage.config.wTotalLength = total_length as u16;
+
+        let config = &storage.config as *const libusb_config_descriptor;
+        unsafe { config_descriptor::from_owned(config, storage) }
+    }
+
+    fn set_attribute(&mut self, bit: u8, set: bool) {
+        if set {
+            self.attributes |= bit;
+        } else {
+            self.attributes &= !bit;
+        }
+    }
+}
+
+impl Default for ConfigDescriptorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds an alternate setting of an interface for a [`ConfigDescriptorBuilder`].
+///
+/// Unset fields describe alternate setting 0 without a class and without endpoints.
+#[derive(Debug, Clone)]
+pub struct InterfaceDescriptorBuilder {
+    number: u8,
+    setting_number: u8,
+    class_code: u8,
+    sub_class_code: u8,
+    protocol_code: u8,
+    description_string_index: u8,
+    extra: Vec<u8>,
+    endpoints: Vec<EndpointDescriptorBuilder>,
+}
+
+impl InterfaceDescriptorBuilder {
+    /// Returns a builder for alternate setting 0 of the interface `number`.
+    pub fn new(number: u8) -> Self {
+        InterfaceDescriptorBuilder {
+            number,
+            setting_number: 0,
+            class_code: 0,
+            sub_class_code: 0,
+            protocol_code: 0,
+            description_string_index: 0,
+            extra: Vec::new(),
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// Sets the alternate setting number, `bAlternateSetting`.
+    pub fn setting_number(&mut self, setting_number: u8) -> &mut Self {
+        self.setting_number = setting_number;
+        self
+    }
+
+    /// Sets the interface class code, `bInterfaceClass`.
+    pub fn class_code(&mut self, class_code: u8) -> &mut Self {
+        self.class_code = class_code;
+        self
+    }
+
+    /// Sets the interface sub class code, `bInterfaceSubClass`.
+    pub fn sub_class_code(&mut self, sub_class_code: u8) -> &mut Self {
+        self.sub_class_code = sub_class_code;
+        self
+    }
+
+    /// Sets the interface protocol code, `bInterfaceProtocol`.
+    pub fn protocol_code(&mut self, protocol_code: u8) -> &mut Self {
+        self.protocol_code = protocol_code;
+        self
+    }
+
+    /// Sets the index of the string descriptor describing the interface, 0 for none.
+    pub fn description_string_index(&mut self, index: u8) -> &mut Self {
+        self.description_string_index = index;
+        self
+    }
+
+    /// Sets the class or vendor specific descriptors following the interface descriptor, e.g.
+    /// a HID descriptor.
+    pub fn extra(&mut self, extra: &[u8]) -> &mut Self {
+        self.extra = extra.to_vec();
+        self
+    }
+
+    /// Adds an endpoint to the alternate setting.
+    pub fn endpoint(&mut self, endpoint: &EndpointDescriptorBuilder) -> &mut Self {
+        self.endpoints.push(endpoint.clone());
+        self
+    }
+
+    fn to_libusb(
+        &self,
+        endpoints: &[libusb_endpoint_descriptor],
+        extras: &mut Vec<Vec<u8>>,
+    ) -> libusb_interface_descriptor {
+        let (extra, extra_length) = keep_extra(&self.extra, extras);
+
+        libusb_interface_descriptor {
+            bLength: LIBUSB_DT_INTERFACE_SIZE,
+            bDescriptorType: LIBUSB_DT_INTERFACE,
+            bInterfaceNumber: self.number,
+            bAlternateSetting: self.setting_number,
+            bNumEndpoints: endpoints.len() as u8,
+            bInterfaceClass: self.class_code,
+            bInterfaceSubClass: self.sub_class_code,
+            bInterfaceProtocol: self.protocol_code,
+            iInterface: self.description_string_index,
+            endpoint: if endpoints.is_empty() {
+                ptr::null()
+            } else {
+                endpoints.as_ptr()
+            },
+            extra,
+            extra_length,
+        }
+    }
+}
+
+/// Builds an endpoint for an [`InterfaceDescriptorBuilder`].
+///
+/// Unset fields describe a bulk endpoint with a maximum packet size of 64 bytes.
+#[derive(Debug, Clone)]
+pub struct EndpointDescriptorBuilder {
+    address: u8,
+    attributes: u8,
+    max_packet_size: u16,
+    interval: u8,
+    extra: Vec<u8>,
+}
+
+impl EndpointDescriptorBuilder {
+    /// Returns a builder for the endpoint with the given address, which includes the direction
+    /// bit, e.g. `0x81` for endpoint 1 IN.
+    pub fn new(address: u8) -> Self {
+        EndpointDescriptorBuilder {
+            address,
+            attributes: LIBUSB_TRANSFER_TYPE_BULK,
+            max_packet_size: 64,
+            interval: 0,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Sets the transfer type, keeping the other bits of `bmAttributes`.
+    pub fn transfer_type(&mut self, transfer_type: TransferType) -> &mut Self {
+        let bits = match transfer_type {
+            TransferType::Control => LIBUSB_TRANSFER_TYPE_CONTROL,
+            TransferType::Isochronous => LIBUSB_TRANSFER_TYPE_ISOCHRONOUS,
+            TransferType::Bulk => LIBUSB_TRANSFER_TYPE_BULK,
+            TransferType::Interrupt => LIBUSB_TRANSFER_TYPE_INTERRUPT,
+        };
+        self.attributes = (self.attributes & !LIBUSB_TRANSFER_TYPE_MASK) | bits;
+        self
+    }
+
+    /// Sets the raw `bmAttributes` field, including the synchronization and usage types of
+    /// isochronous endpoints.
+    pub fn attributes(&mut self, attributes: u8) -> &mut Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Sets the raw `wMaxPacketSize` field.
+    pub fn max_packet_size(&mut self, max_packet_size: u16) -> &mut Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Sets the polling interval, `bInterval`.
+    pub fn interval(&mut self, interval: u8) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the class or vendor specific descriptors following the endpoint descriptor.
+    pub fn extra(&mut self, extra: &[u8]) -> &mut Self {
+        self.extra = extra.to_vec();
+        self
+    }
+
+    fn to_libusb(&self, extras: &mut Vec<Vec<u8>>) -> libusb_endpoint_descriptor {
+        let (extra, extra_length) = keep_extra(&self.extra, extras);
+
+        libusb_endpoint_descriptor {
+            bLength: LIBUSB_DT_ENDPOINT_SIZE,
+            bDescriptorType: LIBUSB_DT_ENDPOINT,
+            bEndpointAddress: self.address,
+            bmAttributes: self.attributes,
+            wMaxPacketSize: self.max_packet_size,
+            bInterval: self.interval,
+            bRefresh: 0,
+            bSynchAddress: 0,
+            extra,
+            extra_length,
+        }
+    }
+}
+
+/// The descriptors of a [`ConfigDescriptor`] built by a [`ConfigDescriptorBuilder`], in place of
+/// the allocation `libusb` would own. The structures point into each other's buffers, which
+/// don't move when the vectors holding them do.
+struct ConfigStorage {
+    config: libusb_config_descriptor,
+    interfaces: Vec<libusb_interface>,
+    settings: Vec<Vec<libusb_interface_descriptor>>,
+    endpoints: Vec<Vec<libusb_endpoint_descriptor>>,
+    extras: Vec<Vec<u8>>,
+}
+
+// The pointers only refer to data owned by the storage itself, which is never modified.
+unsafe impl Send for ConfigStorage {}
+unsafe impl Sync for ConfigStorage {}
+
+/// Moves a copy of `extra` into `extras` and returns the pointer and length for a descriptor.
+fn keep_extra(extra: &[u8], extras: &mut Vec<Vec<u8>>) -> (*const u8, c_int) {
+    if extra.is_empty() {
+        return (ptr::null(), 0);
+    }
+
+    let extra = extra.to_vec();
+    let pointer = (extra.as_ptr(), extra.len() as c_int);
+    extras.push(extra);
+    pointer
+}
+
+/// Encodes a version as a binary coded decimal field, the inverse of [`Version::from_bcd`].
+fn to_bcd(version: Version) -> u16 {
+    let Version(major, minor, sub_minor) = version;
+
+    u16::from(major / 10) << 12
+        | u16::from(major % 10) << 8
+        | u16::from(minor & 0x0F) << 4
+        | u16::from(sub_minor & 0x0F)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        ConfigDescriptorBuilder, DeviceDescriptorBuilder, EndpointDescriptorBuilder,
+        InterfaceDescriptorBuilder,
+    };
+    use crate::fields::{Direction, TransferType, Version};
+
+    #[test]
+    fn it_builds_device_descriptors() {
+        let descriptor = DeviceDescriptorBuilder::new(0x1234, 0x5678)
+            .usb_version(Version(2, 1, 0))
+            .device_version(Version(12, 3, 4))
+            .product_string_index(2)
+            .build();
+
+        assert_eq!(0x1234, descriptor.vendor_id());
+        assert_eq!(0x5678, descriptor.product_id());
+        assert_eq!(Version(2, 1, 0), descriptor.usb_version());
+        assert_eq!(Version(12, 3, 4), descriptor.device_version());
+        assert_eq!(Some(2), descriptor.product_string_index());
+        assert_eq!(None, descriptor.serial_number_string_index());
+        assert_eq!(64, descriptor.max_packet_size());
+    }
+
+    #[test]
+    fn it_groups_alternate_settings_by_interface() {
+        let config = ConfigDescriptorBuilder::new()
+            .max_power(500)
+            .self_powered(true)
+            .interface(&InterfaceDescriptorBuilder::new(0))
+            .interface(
+                InterfaceDescriptorBuilder::new(1).endpoint(
+                    EndpointDescriptorBuilder::new(0x83)
+                        .transfer_type(TransferType::Interrupt)
+                        .interval(10),
+                ),
+            )
+            .interface(
+                InterfaceDescriptorBuilder::new(0)
+                    .setting_number(1)
+                    .extra(&[0x04, 0x24, 0x01, 0x00])
+                    .endpoint(&EndpointDescriptorBuilder::new(0x81))
+                    .endpoint(&EndpointDescriptorBuilder::new(0x02)),
+            )
+            .build();
+
+        assert_eq!(500, config.max_power());
+        assert!(config.self_powered());
+        assert_eq!(2, config.num_interfaces());
+        assert_eq!(9 + 3 * 9 + 4 + 3 * 7, usize::from(config.total_length()));
+        assert_eq!(usize::from(config.total_length()), config.raw().len());
+
+        let interfaces = config.interfaces().collect::<Vec<_>>();
+        assert_eq!(0, interfaces[0].number());
+        assert_eq!(2, interfaces[0].num_alt_settings());
+        assert_eq!(1, interfaces[1].number());
+
+        let setting = interfaces[0].alt_setting(1).unwrap();
+        assert_eq!(&[0x04, 0x24, 0x01, 0x00], setting.extra());
+        assert_eq!(
+            vec![(0x81, Direction::In), (0x02, Direction::Out)],
+            setting
+                .endpoint_descriptors()
+                .map(|endpoint| (endpoint.address(), endpoint.direction()))
+                .collect::<Vec<_>>()
+        );
+
+        let endpoint = interfaces[1]
+            .alt_setting(0)
+            .unwrap()
+            .endpoint_descriptors()
+            .next()
+            .unwrap();
+        assert_eq!(TransferType::Interrupt, endpoint.transfer_type());
+        assert_eq!(10, endpoint.interval());
+    }
+}
@@ -2,6 +2,9 @@ use std::{
     fmt::{self, Debug},
     mem,
     ptr::NonNull,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
 };
 
 use libusb1_sys::*;
@@ -9,19 +12,31 @@ use libusb1_sys::*;
 use crate::{
     config_descriptor::{self, ConfigDescriptor},
     device_descriptor::{self, DeviceDescriptor},
-    device_handle::DeviceHandle,
+    device_handle::{DeviceHandle, ResetOutcome},
     error,
     fields::{self, Speed},
+    port_path::PortPath,
     Error, UsbContext,
 };
 
+/// How long [`Device::reset_via_handle`] waits for a device to reappear after a reset.
+const REENUMERATION_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// A reference to a USB device.
-#[derive(Eq, PartialEq)]
 pub struct Device<T: UsbContext> {
     context: T,
     device: NonNull<libusb_device>,
+    descriptor: OnceLock<DeviceDescriptor>,
 }
 
+impl<T: UsbContext + PartialEq> PartialEq for Device<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.context == other.context && self.device == other.device
+    }
+}
+
+impl<T: UsbContext + Eq> Eq for Device<T> {}
+
 impl<T: UsbContext> Drop for Device<T> {
     /// Releases the device reference.
     fn drop(&mut self) {
@@ -33,10 +48,16 @@ impl<T: UsbContext> Drop for Device<T> {
 
 impl<T: UsbContext> Clone for Device<T> {
     fn clone(&self) -> Self {
-        unsafe { Self::from_libusb(self.context.clone(), self.device) }
+        let device = unsafe { Self::from_libusb(self.context.clone(), self.device) };
+        if let Some(descriptor) = self.descriptor.get() {
+            let _ = device.descriptor.set(descriptor.clone());
+        }
+        device
     }
 }
 
+// libusb devices are reference counted atomically and their accessors only read immutable
+// state, so they may be used from any thread.
 unsafe impl<T: UsbContext> Send for Device<T> {}
 unsafe impl<T: UsbContext> Sync for Device<T> {}
 
@@ -59,6 +80,31 @@ impl<T: UsbContext> Debug for Device<T> {
     }
 }
 
+impl<T: UsbContext> fmt::Display for Device<T> {
+    /// Formats the device the way `lsusb` lists it, e.g. `Bus 003 Device 012 ID 1234:5678`.
+    ///
+    /// If the device descriptor can't be read, the IDs are replaced by
+    /// `(descriptor unavailable)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Bus {:03} Device {:03} ",
+            self.bus_number(),
+            self.address()
+        )?;
+
+        match self.device_descriptor() {
+            Ok(descriptor) => write!(
+                f,
+                "ID {:04x}:{:04x}",
+                descriptor.vendor_id(),
+                descriptor.product_id()
+            ),
+            Err(_) => f.write_str("(descriptor unavailable)"),
+        }
+    }
+}
+
 impl<T: UsbContext> Device<T> {
     /// Get the raw libusb_device pointer, for advanced use in unsafe code
     pub fn as_raw(&self) -> *mut libusb_device {
@@ -77,11 +123,23 @@ impl<T: UsbContext> Device<T> {
     pub unsafe fn from_libusb(context: T, device: NonNull<libusb_device>) -> Device<T> {
         libusb_ref_device(device.as_ptr());
 
-        Device { context, device }
+        Device {
+            context,
+            device,
+            descriptor: OnceLock::new(),
+        }
     }
 
     /// Reads the device descriptor.
+    ///
+    /// The descriptor doesn't change for the lifetime of the device, so it is read once and
+    /// cached in this `Device`; later calls, and clones made afterwards, return the cached copy.
+    /// The cache is per instance: a `Device` obtained from a new enumeration reads it again.
     pub fn device_descriptor(&self) -> crate::Result<DeviceDescriptor> {
+        if let Some(descriptor) = self.descriptor.get() {
+            return Ok(descriptor.clone());
+        }
+
         let mut descriptor = mem::MaybeUninit::<libusb_device_descriptor>::uninit();
 
         // since libusb 1.0.16, this function always succeeds
@@ -90,9 +148,8 @@ impl<T: UsbContext> Device<T> {
             descriptor.as_mut_ptr()
         ));
 
-        Ok(device_descriptor::from_libusb(unsafe {
-            descriptor.assume_init()
-        }))
+        let descriptor = device_descriptor::from_libusb(unsafe { descriptor.assume_init() });
+        Ok(self.descriptor.get_or_init(|| descriptor).clone())
     }
 
     /// Reads a configuration descriptor.
@@ -108,6 +165,31 @@ impl<T: UsbContext> Device<T> {
         Ok(unsafe { config_descriptor::from_libusb(config.assume_init()) })
     }
 
+    /// Returns an iterator over all of the device's configuration descriptors.
+    ///
+    /// Configurations are read lazily, one per `bNumConfigurations` index. A configuration that
+    /// can't be read is yielded as an `Err` item rather than ending the iteration.
+    pub fn config_descriptors(&self) -> impl Iterator<Item = crate::Result<ConfigDescriptor>> + '_ {
+        let num_configurations = self
+            .device_descriptor()
+            .map_or(0, |descriptor| descriptor.num_configurations());
+
+        (0..num_configurations).map(move |index| self.config_descriptor(index))
+    }
+
+    /// Reads all of the device's configuration descriptors.
+    ///
+    /// Unlike [`config_descriptors`](#method.config_descriptors), this reads every
+    /// configuration up front and fails with the first error, including an error reading the
+    /// device descriptor.
+    pub fn all_config_descriptors(&self) -> crate::Result<Vec<ConfigDescriptor>> {
+        let num_configurations = self.device_descriptor()?.num_configurations();
+
+        (0..num_configurations)
+            .map(|index| self.config_descriptor(index))
+            .collect()
+    }
+
     /// Reads the configuration descriptor for the current configuration.
     pub fn active_config_descriptor(&self) -> crate::Result<ConfigDescriptor> {
         let mut config = mem::MaybeUninit::<*const libusb_config_descriptor>::uninit();
@@ -120,6 +202,30 @@ impl<T: UsbContext> Device<T> {
         Ok(unsafe { config_descriptor::from_libusb(config.assume_init()) })
     }
 
+    /// Returns the maximum current in milliamps that the device draws from the bus in its active
+    /// configuration.
+    ///
+    /// The `bMaxPower` field of the configuration descriptor counts in 2 mA units, or in 8 mA
+    /// units when the device operates at SuperSpeed or faster, so this takes the device's
+    /// [speed](#method.speed) into account.
+    ///
+    /// ## Errors
+    ///
+    /// * `NotFound` if the device is unconfigured. It then advertises no requirement and may
+    ///   draw at most one unit load, 100 mA or 150 mA at SuperSpeed.
+    ///
+    /// Otherwise the errors are those of reading the active configuration descriptor.
+    pub fn max_power_draw(&self) -> crate::Result<u16> {
+        let config = self.active_config_descriptor()?;
+
+        // `max_power` has already doubled `bMaxPower`.
+        let units = match self.speed() {
+            Speed::Super | Speed::SuperPlus => 8,
+            _ => 2,
+        };
+        Ok(config.max_power() / 2 * units)
+    }
+
     /// Returns the number of the bus that the device is connected to.
     pub fn bus_number(&self) -> u8 {
         unsafe { libusb_get_bus_number(self.device.as_ptr()) }
@@ -131,11 +237,37 @@ impl<T: UsbContext> Device<T> {
     }
 
     /// Returns the device's connection speed.
+    ///
+    /// The device doesn't need to be opened for this, and it does no I/O: `libusb` records the
+    /// speed when it enumerates the device, so it is cheap to call for every device of a list.
     pub fn speed(&self) -> Speed {
         fields::speed_from_libusb(unsafe { libusb_get_device_speed(self.device.as_ptr()) })
     }
 
     /// Opens the device.
+    ///
+    /// ## Errors
+    ///
+    /// * `Access` if the user lacks the permissions to open the device.
+    /// * `NoDevice` if the device has been disconnected.
+    /// * `NoMem` on memory allocation failure.
+    /// * `NotSupported` on Windows if no driver that `libusb` can use is bound to the device,
+    ///   see below.
+    ///
+    /// On Windows, `libusb` talks to devices through the WinUSB, libusbK or libusb0 drivers,
+    /// and through the HID driver for HID devices. Devices that use a vendor driver or another
+    /// class driver, e.g. for serial ports or mass storage, can't be opened, or fail with
+    /// `NotSupported` on the first operation that needs the driver, such as
+    /// [`claim_interface`](DeviceHandle::claim_interface). This is a driver installation issue
+    /// rather than a bug in the calling code: binding WinUSB to the device, e.g. with
+    /// [Zadig](https://zadig.akeo.ie/) or an INF file shipped with the application, resolves it.
+    ///
+    /// On Windows, a device that another process has opened or whose interface another process
+    /// has claimed also fails with `Access`, either here or from
+    /// [`claim_interface`](DeviceHandle::claim_interface). `libusb` reports both cases with the
+    /// same error, so they can't be told apart from the error alone. A missing or wrong driver
+    /// shows up as `NotSupported` instead; if the device can be opened once no other program
+    /// uses it, the `Access` error was caused by that program.
     pub fn open(&self) -> crate::Result<DeviceHandle<T>> {
         let mut handle = mem::MaybeUninit::<*mut libusb_device_handle>::uninit();
 
@@ -147,19 +279,136 @@ impl<T: UsbContext> Device<T> {
         })
     }
 
-    /// Returns the device's port number
+    /// Opens the device, retrying up to `retries` times while it reports `Busy` or `Access`.
+    ///
+    /// Right after a device is connected or reset, the OS may still be setting up its device
+    /// node, e.g. udev applying permissions on Linux, and opening it fails transiently. This
+    /// waits `delay` between attempts. Other errors are returned immediately, and if every
+    /// attempt fails, the error of the last one is returned.
+    ///
+    /// Since `Access` is retried as well, a device the user can never open takes
+    /// `retries * delay` to fail.
+    pub fn open_with_retry(&self, retries: u32, delay: Duration) -> crate::Result<DeviceHandle<T>> {
+        let mut attempt = 0;
+
+        loop {
+            match self.open() {
+                Err(Error::Busy) | Err(Error::Access) if attempt < retries => {
+                    attempt += 1;
+                    thread::sleep(delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns true if the device can be opened right now.
+    ///
+    /// The device is opened and closed again immediately. This is meant for diagnostics, such as
+    /// checking whether another process holds the device on Windows; a later call to
+    /// [`open`](#method.open) may still fail.
+    pub fn is_openable(&self) -> bool {
+        self.open().is_ok()
+    }
+
+    /// Returns the number of the port on its parent hub that the device is connected to.
+    ///
+    /// This is only the last step of the device's location: port 4 of whichever hub it is
+    /// plugged into. [`port_numbers`](#method.port_numbers) returns the whole chain of ports
+    /// from the root hub, and [`port_path`](#method.port_path) adds the bus number to identify
+    /// the port uniquely. Root hubs, which have no parent, return 0.
+    #[doc(alias = "libusb_get_port_number")]
     pub fn port_number(&self) -> u8 {
         unsafe { libusb_get_port_number(self.device.as_ptr()) }
     }
 
     /// Returns the device's parent
+    ///
+    /// The returned `Device` holds its own reference to the parent, like any other `Device`, so
+    /// it stays valid after the [`DeviceList`](crate::DeviceList) this device came from is
+    /// dropped. `libusb` only guarantees the parent link itself while a device list of the
+    /// context is alive, so call this while holding one, e.g. when walking the topology of a
+    /// freshly enumerated list. Returns `None` for root hubs, and on platforms that don't
+    /// report parents.
     pub fn get_parent(&self) -> Option<Self> {
         let device = unsafe { libusb_get_parent(self.device.as_ptr()) };
         NonNull::new(device)
             .map(|device| unsafe { Device::from_libusb(self.context.clone(), device) })
     }
 
+    /// Resets the device through `handle` and returns a handle that is valid afterwards.
+    ///
+    /// `handle` must be a handle to this device. If the reset completes without the device
+    /// re-enumerating, `handle` is returned unchanged. Otherwise the old handle is closed and the
+    /// device is located again by its bus, port path, vendor ID and product ID, then reopened.
+    /// If the device has a serial number that could be read before the reset, the reopened
+    /// device must also report the same serial number, so that a different device plugged into
+    /// the same port in the meantime isn't mistaken for it.
+    ///
+    /// While the reappeared device fails to open with `Busy` or `Access`, e.g. because udev
+    /// hasn't applied its permissions yet, opening is retried. Returns `Error::NoDevice` if the
+    /// device doesn't reappear within 5 seconds of the reset, or the last `Busy` or `Access`
+    /// error if it reappeared but couldn't be opened by then.
+    pub fn reset_via_handle(&self, handle: DeviceHandle<T>) -> crate::Result<DeviceHandle<T>> {
+        let descriptor = self.device_descriptor()?;
+        let ports = self.port_numbers()?;
+        // A device without a serial number, or one failing to report it, can only be matched by
+        // its location and IDs.
+        let serial = handle.read_serial_number_string_ascii(&descriptor).ok();
+
+        match handle.reset()? {
+            ResetOutcome::Completed => return Ok(handle),
+            ResetOutcome::Reenumerated => drop(handle),
+        }
+
+        let deadline = Instant::now() + REENUMERATION_TIMEOUT;
+        let mut open_error = None;
+        loop {
+            for device in self.context.devices()?.iter() {
+                if device.device == self.device
+                    || device.bus_number() != self.bus_number()
+                    || device.port_numbers().ok().as_ref() != Some(&ports)
+                {
+                    continue;
+                }
+
+                match device.device_descriptor() {
+                    Ok(found)
+                        if found.vendor_id() == descriptor.vendor_id()
+                            && found.product_id() == descriptor.product_id() =>
+                    {
+                        // Right after re-enumeration the OS may still be setting up the
+                        // device node, so these errors only mean it isn't ready yet.
+                        let handle = match device.open() {
+                            Ok(handle) => handle,
+                            Err(e @ Error::Busy) | Err(e @ Error::Access) => {
+                                open_error = Some(e);
+                                continue;
+                            }
+                            Err(e) => return Err(e),
+                        };
+                        let found_serial = match &serial {
+                            Some(_) => handle.read_serial_number_string_ascii(&found).ok(),
+                            None => None,
+                        };
+                        if found_serial == serial {
+                            return Ok(handle);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return Err(open_error.unwrap_or(Error::NoDevice));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
     ///  Get the list of all port numbers from root for the specified device
+    ///
+    /// The last entry is the [`port_number`](#method.port_number) of the device itself.
     pub fn port_numbers(&self) -> Result<Vec<u8>, Error> {
         // As per the USB 3.0 specs, the current maximum limit for the depth is 7.
         let mut ports = [0; 7];
@@ -175,4 +424,14 @@ impl<T: UsbContext> Device<T> {
         };
         Ok(ports[0..ports_number as usize].to_vec())
     }
+
+    /// Returns the device's physical location, made of its bus number and
+    /// [port numbers](#method.port_numbers).
+    ///
+    /// The port path stays the same while the device is connected to the same port, so it can
+    /// be used to identify devices across reconnections where the
+    /// [address](#method.address) changes.
+    pub fn port_path(&self) -> Result<PortPath, Error> {
+        Ok(PortPath::new(self.bus_number(), self.port_numbers()?))
+    }
 }
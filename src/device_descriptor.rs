@@ -1,10 +1,22 @@
-use std::fmt;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 
 use libusb1_sys::*;
 
-use crate::fields::Version;
+use crate::fields::{Speed, Version};
 
 /// Describes a device.
+///
+/// Descriptors compare equal and hash alike when they identify the same kind of device: the
+/// vendor and product IDs, the device version and the class, sub class and protocol codes all
+/// match. The remaining fields are deliberately left out. String descriptor indices are chosen
+/// freely by the firmware and say nothing about which device it is. `bcdUSB` and
+/// `bMaxPacketSize0` depend on how the device is connected: the same device reports different
+/// values on a USB 2 and a USB 3 port, and it should still compare equal. `bLength` and
+/// `bDescriptorType` are the same for every device descriptor, and the number of configurations
+/// is fixed by the firmware that the IDs and device version already identify.
 pub struct DeviceDescriptor {
     descriptor: libusb_device_descriptor,
 }
@@ -25,6 +37,23 @@ impl DeviceDescriptor {
         Version::from_bcd(self.descriptor.bcdUSB)
     }
 
+    /// Returns the highest speed the device's USB version allows, as a fallback for when the
+    /// operating system reports [`Speed::Unknown`] for the connection.
+    ///
+    /// This is a capability derived from `bcdUSB`, not the negotiated link speed, and only an
+    /// upper bound: a USB 2.0 device may still be limited to full speed, and a USB 1.x device to
+    /// low speed. USB 3.x devices are reported as [`Speed::Super`], since `bcdUSB` doesn't tell
+    /// whether they support the faster SuperSpeed+ rates. Versions below 1.0 give
+    /// [`Speed::Unknown`].
+    pub fn max_supported_speed(&self) -> Speed {
+        match self.descriptor.bcdUSB {
+            0x0300..=0xFFFF => Speed::Super,
+            0x0200..=0x02FF => Speed::High,
+            0x0100..=0x01FF => Speed::Full,
+            _ => Speed::Unknown,
+        }
+    }
+
     /// Returns the manufacturer's version of the device.
     pub fn device_version(&self) -> Version {
         Version::from_bcd(self.descriptor.bcdDevice)
@@ -80,14 +109,85 @@ impl DeviceDescriptor {
     }
 
     /// Returns the maximum packet size of the device's first endpoint.
+    ///
+    /// This is the raw `bMaxPacketSize0` field; see
+    /// [`control_max_packet_size`](#method.control_max_packet_size) for the size in bytes.
     pub fn max_packet_size(&self) -> u8 {
         self.descriptor.bMaxPacketSize0
     }
 
+    /// Returns the maximum packet size in bytes of endpoint 0, the default control endpoint.
+    ///
+    /// This only applies to control transfers on endpoint 0; the other endpoints declare their
+    /// packet sizes in their endpoint descriptors. Devices up to USB 2.x store the size itself
+    /// in `bMaxPacketSize0`, one of 8, 16, 32 or 64. USB 3.x devices store it as a power of two
+    /// instead, where the only valid exponent 9 means 512 bytes, which is why this returns a
+    /// `u16`. An exponent too large for a `u16` gives 0.
+    pub fn control_max_packet_size(&self) -> u16 {
+        let value = self.descriptor.bMaxPacketSize0;
+
+        match self.descriptor.bcdUSB {
+            0x0300..=0xFFFF => 1u16.checked_shl(u32::from(value)).unwrap_or(0),
+            _ => u16::from(value),
+        }
+    }
+
     /// Returns the number of config descriptors available for the device.
     pub fn num_configurations(&self) -> u8 {
         self.descriptor.bNumConfigurations
     }
+
+    fn identity(&self) -> (u16, u16, u16, u8, u8, u8) {
+        let d = &self.descriptor;
+
+        (
+            d.idVendor,
+            d.idProduct,
+            d.bcdDevice,
+            d.bDeviceClass,
+            d.bDeviceSubClass,
+            d.bDeviceProtocol,
+        )
+    }
+}
+
+impl PartialEq for DeviceDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for DeviceDescriptor {}
+
+impl Hash for DeviceDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
+    }
+}
+
+impl Clone for DeviceDescriptor {
+    fn clone(&self) -> Self {
+        let d = &self.descriptor;
+
+        DeviceDescriptor {
+            descriptor: libusb_device_descriptor {
+                bLength: d.bLength,
+                bDescriptorType: d.bDescriptorType,
+                bcdUSB: d.bcdUSB,
+                bDeviceClass: d.bDeviceClass,
+                bDeviceSubClass: d.bDeviceSubClass,
+                bDeviceProtocol: d.bDeviceProtocol,
+                bMaxPacketSize0: d.bMaxPacketSize0,
+                idVendor: d.idVendor,
+                idProduct: d.idProduct,
+                bcdDevice: d.bcdDevice,
+                iManufacturer: d.iManufacturer,
+                iProduct: d.iProduct,
+                iSerialNumber: d.iSerialNumber,
+                bNumConfigurations: d.bNumConfigurations,
+            },
+        }
+    }
 }
 
 impl fmt::Debug for DeviceDescriptor {
@@ -120,7 +220,9 @@ pub fn from_libusb(device: libusb_device_descriptor) -> DeviceDescriptor {
 
 #[cfg(test)]
 mod test {
-    use crate::fields::Version;
+    use std::collections::HashSet;
+
+    use crate::fields::{Speed, Version};
 
     #[test]
     fn it_has_usb_version() {
@@ -234,6 +336,104 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_decodes_control_max_packet_size() {
+        let size = |bcd, value| {
+            super::from_libusb(device_descriptor!(bcdUSB: bcd, bMaxPacketSize0: value))
+                .control_max_packet_size()
+        };
+
+        assert_eq!(8, size(0x0110, 8));
+        assert_eq!(64, size(0x0200, 64));
+        assert_eq!(512, size(0x0300, 9));
+        assert_eq!(512, size(0x0320, 9));
+        assert_eq!(0, size(0x0300, 16));
+    }
+
+    #[test]
+    fn it_clones_all_fields() {
+        let descriptor = super::from_libusb(device_descriptor!(
+            bcdUSB: 0x0200,
+            idVendor: 0x1d6b,
+            idProduct: 0x0002,
+            iSerialNumber: 3,
+            bNumConfigurations: 2
+        ));
+
+        assert_eq!(
+            format!("{:?}", descriptor),
+            format!("{:?}", descriptor.clone())
+        );
+    }
+
+    #[test]
+    fn it_compares_identity_fields() {
+        let descriptor = super::from_libusb(device_descriptor!(
+            idVendor: 0x1d6b,
+            idProduct: 0x0002,
+            bcdDevice: 0x0515,
+            bDeviceClass: 9
+        ));
+
+        assert_eq!(
+            descriptor,
+            super::from_libusb(device_descriptor!(
+                idVendor: 0x1d6b,
+                idProduct: 0x0002,
+                bcdDevice: 0x0515,
+                bDeviceClass: 9,
+                iProduct: 2,
+                iSerialNumber: 1
+            ))
+        );
+        assert_ne!(
+            descriptor,
+            super::from_libusb(device_descriptor!(
+                idVendor: 0x1d6b,
+                idProduct: 0x0003,
+                bcdDevice: 0x0515,
+                bDeviceClass: 9
+            ))
+        );
+        assert_ne!(
+            descriptor,
+            super::from_libusb(device_descriptor!(
+                idVendor: 0x1d6b,
+                idProduct: 0x0002,
+                bcdDevice: 0x0600,
+                bDeviceClass: 9
+            ))
+        );
+    }
+
+    #[test]
+    fn it_hashes_identity_fields() {
+        let mut registry = HashSet::new();
+
+        registry.insert(super::from_libusb(
+            device_descriptor!(idVendor: 0x1d6b, idProduct: 0x0002, iSerialNumber: 1),
+        ));
+
+        assert!(registry.contains(&super::from_libusb(
+            device_descriptor!(idVendor: 0x1d6b, idProduct: 0x0002, iSerialNumber: 4)
+        )));
+        assert!(!registry.contains(&super::from_libusb(
+            device_descriptor!(idVendor: 0x1d6b, idProduct: 0x0003)
+        )));
+    }
+
+    #[test]
+    fn it_derives_max_supported_speed_from_usb_version() {
+        let speed = |bcd| super::from_libusb(device_descriptor!(bcdUSB: bcd)).max_supported_speed();
+
+        assert_eq!(Speed::Unknown, speed(0x0000));
+        assert_eq!(Speed::Full, speed(0x0110));
+        assert_eq!(Speed::High, speed(0x0200));
+        assert_eq!(Speed::High, speed(0x0210));
+        assert_eq!(Speed::Super, speed(0x0300));
+        assert_eq!(Speed::Super, speed(0x0320));
+    }
+
     #[test]
     fn it_has_num_configurations() {
         assert_eq!(
@@ -1,25 +1,39 @@
 use std::{
     fmt::{self, Debug},
+    io::IoSlice,
     mem,
-    ptr::NonNull,
-    sync::Mutex,
-    time::Duration,
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError, RwLock, RwLockReadGuard, TryLockError,
+    },
+    time::{Duration, Instant},
 };
 
 use libc::{c_int, c_uchar, c_uint};
 use libusb1_sys::{constants::*, *};
 
 use crate::{
+    bulk_frames::BulkFrames,
     config_descriptor::ConfigDescriptor,
     device::{self, Device},
     device_descriptor::DeviceDescriptor,
-    error::{self, Error},
-    fields::{request_type, Direction, Recipient, RequestType},
+    dma_buffer::{self, DmaBuffer},
+    error::{self, reason, Error, SequenceError},
+    fields::{
+        self, descriptor_value, interface_index, request_type, ControlSetup, DeviceStatus,
+        Direction, Recipient, RequestType, Speed, TransferType,
+    },
     interface_descriptor::InterfaceDescriptor,
-    language::Language,
+    language::{Language, PrimaryLanguage},
+    transfer::Transfer,
     UsbContext,
 };
 
+/// Largest piece of a buffer submitted in a single transfer by
+/// [`DeviceHandle::write_bulk_chunked`].
+const MAX_BULK_CHUNK: usize = 1024 * 1024;
+
 /// Bit set representing claimed USB interfaces.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 struct ClaimedInterfaces {
@@ -109,15 +123,115 @@ impl<'a> Iterator for ClaimedInterfacesIter<'a> {
     }
 }
 
+/// The buffer of a transfer, tagged with the direction it is used in.
+#[derive(Debug)]
+pub enum BufKind<'a> {
+    /// Buffer to fill with data read from the device.
+    In(&'a mut [u8]),
+
+    /// Data to write to the device.
+    Out(&'a [u8]),
+}
+
+/// The outcome of a successful [`DeviceHandle::reset`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[must_use]
+pub enum ResetOutcome {
+    /// The device was reset and the handle is still valid.
+    Completed,
+
+    /// The device re-enumerated during the reset, for example because its descriptors changed.
+    /// The handle no longer refers to it; the device has to be found and opened again.
+    Reenumerated,
+}
+
+/// A claimed interface that is released when the guard is dropped.
+///
+/// Returned by [`DeviceHandle::claim_interface_guard`]. The guard borrows the handle, which can
+/// still be used for transfers on the interface while the guard is alive.
+#[derive(Debug)]
+#[must_use = "the interface is released as soon as the guard is dropped"]
+pub struct InterfaceGuard<'a, T: UsbContext> {
+    handle: &'a DeviceHandle<T>,
+    iface: u8,
+}
+
+impl<'a, T: UsbContext> InterfaceGuard<'a, T> {
+    /// Returns the number of the claimed interface.
+    pub fn interface(&self) -> u8 {
+        self.iface
+    }
+
+    /// Returns the handle the interface was claimed through.
+    pub fn handle(&self) -> &'a DeviceHandle<T> {
+        self.handle
+    }
+
+    /// Releases the interface now and returns the result, which dropping the guard ignores.
+    pub fn release(self) -> crate::Result<()> {
+        let result = self.handle.release_interface(self.iface);
+        mem::forget(self);
+        result
+    }
+}
+
+impl<'a, T: UsbContext> Drop for InterfaceGuard<'a, T> {
+    /// Releases the interface.
+    fn drop(&mut self) {
+        let _ = self.handle.release_interface(self.iface);
+    }
+}
+
+/// Counters of the transfers performed through a [`DeviceHandle`].
+///
+/// See [`DeviceHandle::set_transfer_stats`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    /// The number of transfers performed, including failed ones.
+    pub transfers: u64,
+
+    /// The number of bytes read or written.
+    pub bytes: u64,
+
+    /// The number of transfers that failed.
+    pub errors: u64,
+}
+
 /// A handle to an open USB device.
+///
+/// ## Thread safety
+///
+/// `DeviceHandle` is `Send` and `Sync`, and all of its methods take `&self` unless stated
+/// otherwise, so a handle can be shared between threads, e.g. in an `Arc`. This relies on the
+/// guarantees of libusb itself: its functions may be called concurrently from several threads,
+/// including synchronous transfers on the same handle, which each submit their own transfer and
+/// wait for its completion. The set of claimed interfaces tracked by the handle is protected by a
+/// mutex.
+///
+/// What libusb does not order is the device's view of concurrent requests. Two threads writing
+/// to the same endpoint at the same time produce interleaved transfers in an unspecified order,
+/// and changing the configuration or alternate setting while transfers are in flight makes them
+/// fail. Such sequences need synchronization by the caller. This also applies to methods that
+/// issue a sequence of requests, like
+/// [`set_alternate_setting_verified`](#method.set_alternate_setting_verified): they don't stop
+/// other threads from using the handle in between.
 pub struct DeviceHandle<T: UsbContext> {
     context: T,
     handle: Option<NonNull<libusb_device_handle>>,
     interfaces: Mutex<ClaimedInterfaces>,
+    auto_detach: AtomicBool,
+    stats: Mutex<Option<TransferStats>>,
+    active_config: Mutex<Option<u8>>,
+    transfers: RwLock<()>,
 }
 
 impl<T: UsbContext> Drop for DeviceHandle<T> {
     /// Closes the device.
+    ///
+    /// Claimed interfaces are released and the handle is closed before the handle's reference
+    /// to its context is dropped, so this always happens against a live context. Closing a handle
+    /// never exits the context; a [`Context`](crate::Context) is exited once its last reference
+    /// is gone, and the global context never is.
     fn drop(&mut self) {
         unsafe {
             let interfaces = self.interfaces.lock().unwrap();
@@ -132,6 +246,7 @@ impl<T: UsbContext> Drop for DeviceHandle<T> {
     }
 }
 
+// libusb device handles may be used from any thread; see "Thread safety" above.
 unsafe impl<T: UsbContext> Send for DeviceHandle<T> {}
 unsafe impl<T: UsbContext> Sync for DeviceHandle<T> {}
 
@@ -141,6 +256,7 @@ impl<T: UsbContext> Debug for DeviceHandle<T> {
             .field("device", &self.device())
             .field("handle", &self.handle)
             .field("interfaces", &*self.interfaces.lock().unwrap())
+            .field("auto_detach", &self.auto_detach_kernel_driver())
             .finish()
     }
 }
@@ -209,36 +325,247 @@ impl<T: UsbContext> DeviceHandle<T> {
             context,
             handle: Some(handle),
             interfaces: Mutex::new(ClaimedInterfaces::new()),
+            auto_detach: AtomicBool::new(false),
+            stats: Mutex::new(None),
+            active_config: Mutex::new(None),
+            transfers: RwLock::new(()),
         }
     }
 
-    /// Returns the active configuration number.
+    /// Returns the active configuration number, or 0 if the device is unconfigured.
+    ///
+    /// The value is queried once and then cached by the handle, since `libusb` may have to ask
+    /// the device with a control transfer, e.g. on Windows and macOS. The cache is cleared by
+    /// [`set_active_configuration`](#method.set_active_configuration),
+    /// [`unconfigure`](#method.unconfigure) and [`reset`](#method.reset). A configuration
+    /// change made elsewhere, e.g. by another process or the kernel, goes unnoticed; use
+    /// [`active_configuration_uncached`](#method.active_configuration_uncached) where that
+    /// matters.
     pub fn active_configuration(&self) -> crate::Result<u8> {
+        if let Some(config) = *self.active_config.lock().unwrap() {
+            return Ok(config);
+        }
+
+        self.active_configuration_uncached()
+    }
+
+    /// Queries the active configuration number from `libusb`, bypassing the cache of
+    /// [`active_configuration`](#method.active_configuration), and updates the cache with the
+    /// result.
+    pub fn active_configuration_uncached(&self) -> crate::Result<u8> {
         let mut config = mem::MaybeUninit::<c_int>::uninit();
 
         try_unsafe!(libusb_get_configuration(self.as_raw(), config.as_mut_ptr()));
-        Ok(unsafe { config.assume_init() } as u8)
+        let config = unsafe { config.assume_init() } as u8;
+
+        *self.active_config.lock().unwrap() = Some(config);
+        Ok(config)
     }
 
     /// Sets the device's active configuration.
+    ///
+    /// A configuration change releases every interface on the device, so it is refused while
+    /// this handle has interfaces claimed: `Error::Busy` is returned and the configuration is left
+    /// unchanged. Release the interfaces first, for example with
+    /// [`release_all_interfaces`](#method.release_all_interfaces), and claim them again
+    /// afterwards.
     pub fn set_active_configuration(&self, config: u8) -> crate::Result<()> {
-        try_unsafe!(libusb_set_configuration(self.as_raw(), c_int::from(config)));
-        Ok(())
+        self.set_configuration(c_int::from(config))
     }
 
     /// Puts the device in an unconfigured state.
+    ///
+    /// Like [`set_active_configuration`](#method.set_active_configuration), this returns
+    /// `Error::Busy` while this handle has interfaces claimed.
     pub fn unconfigure(&self) -> crate::Result<()> {
-        try_unsafe!(libusb_set_configuration(self.as_raw(), -1));
+        self.set_configuration(-1)
+    }
+
+    fn set_configuration(&self, config: c_int) -> crate::Result<()> {
+        // The lock is held until the configuration is set, so no interface can be claimed in
+        // between; `claim_interface` holds it while claiming as well.
+        let interfaces = self.interfaces.lock().unwrap();
+        if interfaces.size() != 0 {
+            return Err(Error::Busy);
+        }
+
+        *self.active_config.lock().unwrap() = None;
+        try_unsafe!(libusb_set_configuration(self.as_raw(), config));
+        drop(interfaces);
         Ok(())
     }
 
     /// Resets the device.
-    pub fn reset(&self) -> crate::Result<()> {
-        try_unsafe!(libusb_reset_device(self.as_raw()));
-        Ok(())
+    ///
+    /// A reset that makes the device re-enumerate, which `libusb` reports as `NotFound`, is
+    /// not an error: it returns [`ResetOutcome::Reenumerated`] to tell the caller to discard this
+    /// handle and open the device again.
+    /// [`Device::reset_via_handle`](crate::Device::reset_via_handle) does that automatically.
+    ///
+    /// `libusb` doesn't support resetting a device while transfers on it are in flight. Since
+    /// the handle can be shared between threads, this is checked at runtime: if another thread
+    /// is in the middle of a transfer through this handle, `Error::Busy` is returned and the
+    /// device is left alone. Transfers started while the reset is in progress wait for it to
+    /// finish. Transfers submitted through the raw handle are not tracked.
+    pub fn reset(&self) -> crate::Result<ResetOutcome> {
+        let _transfers = match self.transfers.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => return Err(Error::Busy),
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        };
+
+        *self.active_config.lock().unwrap() = None;
+        match unsafe { libusb_reset_device(self.as_raw()) } {
+            0 => Ok(ResetOutcome::Completed),
+            LIBUSB_ERROR_NOT_FOUND => Ok(ResetOutcome::Reenumerated),
+            err => Err(error::from_libusb(err)),
+        }
+    }
+
+    /// Marks a transfer through this handle as in flight until the guard is dropped, which
+    /// makes [`reset`](#method.reset) refuse to run meanwhile.
+    fn in_flight(&self) -> RwLockReadGuard<'_, ()> {
+        self.transfers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Indicates whether the device is currently runtime-suspended by the operating system.
+    ///
+    /// libusb does not expose link power states, so this is a best-effort query of the
+    /// kernel's runtime power management status. It is only supported on Linux, where it reads
+    /// `power/runtime_status` of the device in sysfs. On other platforms, or when the kernel
+    /// doesn't provide the attribute, `Error::NotSupported` is returned. If the device is gone
+    /// from sysfs, e.g. because it was just unplugged, `Error::NoDevice` is returned.
+    pub fn is_suspended(&self) -> crate::Result<bool> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            use std::{io::ErrorKind, path::Path};
+
+            const SYSFS_USB_DEVICES: &str = "/sys/bus/usb/devices";
+
+            let device = format!("{}/{}", SYSFS_USB_DEVICES, self.device().port_path()?);
+
+            match std::fs::read_to_string(Path::new(&device).join("power/runtime_status")) {
+                Ok(status) => Ok(status.trim() == "suspended"),
+                Err(err) if err.kind() == ErrorKind::PermissionDenied => Err(Error::Access),
+                // Without sysfs or the attribute this can't be answered, but a device missing
+                // from a mounted sysfs has been disconnected.
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    if Path::new(&device).exists() || !Path::new(SYSFS_USB_DEVICES).exists() {
+                        Err(Error::NotSupported)
+                    } else {
+                        Err(Error::NoDevice)
+                    }
+                }
+                Err(_) => Err(Error::Io),
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            Err(Error::NotSupported)
+        }
+    }
+
+    /// Reads the status of the device with a standard `GET_STATUS` request.
+    pub fn device_status(&self, timeout: Duration) -> crate::Result<DeviceStatus> {
+        let mut buf = [0u8; 2];
+
+        let len = self.read_control(
+            request_type(Direction::In, RequestType::Standard, Recipient::Device),
+            LIBUSB_REQUEST_GET_STATUS,
+            0,
+            0,
+            &mut buf,
+            timeout,
+        )?;
+
+        match len {
+            2 => Ok(DeviceStatus(u16::from_le_bytes(buf))),
+            _ => Err(Error::Io),
+        }
+    }
+
+    /// Returns true if the device currently runs on its own power supply, from the
+    /// `Self Powered` bit of a standard `GET_STATUS` request.
+    ///
+    /// This is the live power state. [`ConfigDescriptor::self_powered`] only tells what the
+    /// configuration advertises in `bmAttributes`. For a device that can run from either bus
+    /// or its own supply, the live state can differ from what is advertised. A device may report
+    /// bus power here when its external supply is unplugged, for example.
+    ///
+    /// This is a shorthand for [`device_status`](#method.device_status) followed by
+    /// [`DeviceStatus::self_powered`], and fails in the same cases.
+    ///
+    /// [`ConfigDescriptor::self_powered`]: crate::ConfigDescriptor::self_powered
+    pub fn is_self_powered(&self, timeout: Duration) -> crate::Result<bool> {
+        Ok(self.device_status(timeout)?.self_powered())
+    }
+
+    /// Allows or forbids the device to signal a remote wakeup, with a standard `SET_FEATURE` or
+    /// `CLEAR_FEATURE` request for `DEVICE_REMOTE_WAKEUP`.
+    ///
+    /// Devices that don't support remote wakeup stall the request, which is reported as
+    /// `Error::Pipe`. The current state can be read back with
+    /// [`device_status`](#method.device_status).
+    pub fn set_remote_wakeup(&self, enable: bool, timeout: Duration) -> crate::Result<()> {
+        let request = if enable {
+            LIBUSB_REQUEST_SET_FEATURE
+        } else {
+            LIBUSB_REQUEST_CLEAR_FEATURE
+        };
+
+        self.control_no_data(
+            ControlSetup {
+                request_type: request_type(
+                    Direction::Out,
+                    RequestType::Standard,
+                    Recipient::Device,
+                ),
+                request,
+                value: DEVICE_REMOTE_WAKEUP,
+                index: 0,
+                length: 0,
+            },
+            timeout,
+        )
+    }
+
+    /// Puts the device, or a port of a hub, into an electrical test mode for compliance
+    /// testing.
+    ///
+    /// With `port` 0 this sends the standard `SET_FEATURE(TEST_MODE)` request to the device,
+    /// with `test_selector` in the high byte of `wIndex`. Otherwise the device must be a hub, and
+    /// the hub class request `SET_FEATURE(PORT_TEST)` puts its downstream port `port` into test
+    /// mode, with the selector in the high byte of `wIndex` and the port in the low byte. The
+    /// selectors defined by USB 2.0 are 1 `Test_J`, 2 `Test_K`, 3 `Test_SE0_NAK`, 4
+    /// `Test_Packet` and 5 `Test_Force_Enable`; 0xC0 to 0xFF are vendor-specific.
+    ///
+    /// The test mode starts after the request completes and can only be left by power cycling
+    /// the device, or for a hub port, by resetting the hub. The device or port doesn't answer
+    /// any other request until then.
+    ///
+    /// ## Errors
+    ///
+    /// Devices and hubs that don't support the selector stall the request, which is reported
+    /// as `Error::Pipe`. Otherwise the errors are the same as for
+    /// [`control_no_data`](#method.control_no_data).
+    pub fn set_test_mode(
+        &self,
+        test_selector: u8,
+        port: u8,
+        timeout: Duration,
+    ) -> crate::Result<()> {
+        self.control_no_data(test_mode_setup(test_selector, port), timeout)
     }
 
     /// Clear the halt/stall condition for an endpoint.
+    ///
+    /// This only needs a shared reference, so a stall can be cleared from any thread holding
+    /// the handle, e.g. through an `Arc<DeviceHandle>`. The call blocks while the
+    /// `CLEAR_FEATURE(ENDPOINT_HALT)` request is sent. No other transfer may be in flight on
+    /// `endpoint` while the halt is cleared; cancel or complete them first.
     pub fn clear_halt(&self, endpoint: u8) -> crate::Result<()> {
         try_unsafe!(libusb_clear_halt(self.as_raw(), endpoint));
         Ok(())
@@ -277,30 +604,187 @@ impl<T: UsbContext> DeviceHandle<T> {
         Ok(())
     }
 
+    /// Detaches the kernel drivers from every interface of the active configuration.
+    ///
+    /// Composite devices often have a kernel driver bound to several interfaces, all of which
+    /// need to be detached before the interfaces can be claimed. This checks each interface with
+    /// [`kernel_driver_active`](#method.kernel_driver_active) and detaches the active drivers.
+    ///
+    /// Returns the numbers of the interfaces whose driver was detached, so that they can be
+    /// reattached with [`attach_kernel_driver`](#method.attach_kernel_driver) when done.
+    ///
+    /// ## Errors
+    ///
+    /// * `NotSupported` if the platform can't detach kernel drivers.
+    ///
+    /// Otherwise the errors are those of reading the active configuration descriptor and of
+    /// detaching a driver. If detaching fails, the drivers detached up to then are reattached
+    /// before the error is returned.
+    pub fn detach_all_kernel_drivers(&self) -> crate::Result<Vec<u8>> {
+        let config = self.device().active_config_descriptor()?;
+        let mut detached = Vec::new();
+
+        for iface in config.interfaces().map(|interface| interface.number()) {
+            let result = match self.kernel_driver_active(iface) {
+                Ok(true) => self
+                    .detach_kernel_driver(iface)
+                    .map(|()| detached.push(iface)),
+                Ok(false) => Ok(()),
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                for &iface in &detached {
+                    let _ = self.attach_kernel_driver(iface);
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(detached)
+    }
+
     /// Enable/disable automatic kernel driver detachment.
     ///
     /// When this is enabled rusb will automatically detach the
     /// kernel driver on an interface when claiming the interface, and
     /// attach it when releasing the interface.
     ///
-    /// On platforms which do not have support, this function will
-    /// return `Error::NotSupported`, and rusb will continue as if
-    /// this function was never called.
-    pub fn set_auto_detach_kernel_driver(&self, auto_detach: bool) -> crate::Result<()> {
-        try_unsafe!(libusb_set_auto_detach_kernel_driver(
-            self.as_raw(),
-            auto_detach.into()
-        ));
-        Ok(())
+    /// Returns `Ok(true)` if the platform honored the request and `Ok(false)` if automatic
+    /// detachment is not supported on this platform, in which case rusb continues as if this
+    /// function was never called. The requested state can be read back with
+    /// [`auto_detach_kernel_driver`](#method.auto_detach_kernel_driver).
+    pub fn set_auto_detach_kernel_driver(&self, auto_detach: bool) -> crate::Result<bool> {
+        match unsafe { libusb_set_auto_detach_kernel_driver(self.as_raw(), auto_detach.into()) } {
+            0 => {
+                self.auto_detach.store(auto_detach, Ordering::Relaxed);
+                Ok(true)
+            }
+            LIBUSB_ERROR_NOT_SUPPORTED => Ok(false),
+            err => Err(error::from_libusb(err)),
+        }
+    }
+
+    /// Indicates whether automatic kernel driver detachment is enabled on this handle.
+    ///
+    /// This is `false` until a call to
+    /// [`set_auto_detach_kernel_driver`](#method.set_auto_detach_kernel_driver) enabling it has
+    /// been honored by the platform.
+    pub fn auto_detach_kernel_driver(&self) -> bool {
+        self.auto_detach.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables counting the transfers performed through this handle.
+    ///
+    /// While enabled, every transfer performed through the handle's methods is counted: the
+    /// synchronous transfers such as [`read_bulk`](#method.read_bulk) and
+    /// [`write_control`](#method.write_control), those of
+    /// [`control_no_data`](#method.control_no_data), and the asynchronous bulk transfers of
+    /// methods such as [`read_bulk_cancelable`](#method.read_bulk_cancelable) and
+    /// [`write_bulk_partial`](#method.write_bulk_partial). This includes the transfers those
+    /// methods make on behalf of other methods of the handle, e.g.
+    /// [`set_remote_wakeup`](#method.set_remote_wakeup). For methods that report partial
+    /// transfers, the bytes moved by a failed transfer are counted too. Counting is disabled by
+    /// default; enabling it starts from zero, and disabling it discards the counters.
+    pub fn set_transfer_stats(&self, enable: bool) {
+        *self.stats.lock().unwrap() = if enable {
+            Some(TransferStats::default())
+        } else {
+            None
+        };
+    }
+
+    /// Returns the transfer counters collected since
+    /// [`set_transfer_stats`](#method.set_transfer_stats) enabled them, or all zeros if they are
+    /// disabled.
+    pub fn stats(&self) -> TransferStats {
+        self.stats.lock().unwrap().unwrap_or_default()
+    }
+
+    /// Adds the outcome of a transfer to the counters, if enabled, and passes it through.
+    fn record_transfer(&self, result: crate::Result<usize>) -> crate::Result<usize> {
+        if let Some(stats) = self.stats.lock().unwrap().as_mut() {
+            stats.transfers += 1;
+            match result {
+                Ok(n) => stats.bytes += n as u64,
+                Err(_) => stats.errors += 1,
+            }
+        }
+
+        result
+    }
+
+    /// Adds the outcome of a transfer that reports the bytes transferred even if it failed to
+    /// the counters, if enabled, and passes it through.
+    fn record_partial_transfer(
+        &self,
+        outcome: (usize, crate::Result<()>),
+    ) -> (usize, crate::Result<()>) {
+        if let Some(stats) = self.stats.lock().unwrap().as_mut() {
+            stats.transfers += 1;
+            stats.bytes += outcome.0 as u64;
+            if outcome.1.is_err() {
+                stats.errors += 1;
+            }
+        }
+
+        outcome
     }
 
     /// Claims one of the device's interfaces.
     ///
     /// An interface must be claimed before operating on it. All claimed interfaces are released
     /// when the device handle goes out of scope.
+    ///
+    /// On Windows, `NotSupported` means that no driver `libusb` can use, such as WinUSB, is
+    /// bound to the interface. See [`Device::open`] for how to fix that.
     pub fn claim_interface(&self, iface: u8) -> crate::Result<()> {
+        // Holding the lock across the claim keeps it from slipping in while
+        // `set_active_configuration` is running.
+        let mut interfaces = self.interfaces.lock().unwrap();
         try_unsafe!(libusb_claim_interface(self.as_raw(), c_int::from(iface)));
-        self.interfaces.lock().unwrap().insert(iface);
+        interfaces.insert(iface);
+        Ok(())
+    }
+
+    /// Claims an interface for the lifetime of the returned guard.
+    ///
+    /// The interface is released when the guard is dropped, including on early returns, so
+    /// it can be claimed for a scope without releasing it by hand:
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use rusb::{Context, UsbContext};
+    ///
+    /// # fn main() -> rusb::Result<()> {
+    /// let handle = Context::new()?.find_and_open(0x1234, 0x5678)?;
+    /// let interface = handle.claim_interface_guard(0)?;
+    /// handle.write_bulk(0x01, b"ping", Duration::from_secs(1))?;
+    /// interface.release()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn claim_interface_guard(&self, iface: u8) -> crate::Result<InterfaceGuard<'_, T>> {
+        self.claim_interface(iface)?;
+        Ok(InterfaceGuard {
+            handle: self,
+            iface,
+        })
+    }
+
+    /// Claims an interface and selects one of its alternate settings.
+    ///
+    /// An interface keeps whatever setting it was left in, so drivers usually select a known
+    /// setting right after claiming it. If selecting the setting fails, the interface is released
+    /// again and the error is returned, so it is either claimed in `setting` or not claimed at all.
+    pub fn claim_interface_with_setting(&self, iface: u8, setting: u8) -> crate::Result<()> {
+        self.claim_interface(iface)?;
+
+        if let Err(err) = self.set_alternate_setting(iface, setting) {
+            let _ = self.release_interface(iface);
+            return Err(err);
+        }
+
         Ok(())
     }
 
@@ -311,7 +795,57 @@ impl<T: UsbContext> DeviceHandle<T> {
         Ok(())
     }
 
+    /// Releases all interfaces claimed through this handle.
+    ///
+    /// Interfaces are otherwise released silently when the handle is dropped. This lets the
+    /// caller release them at a well-defined point and observe failures. An interface that libusb
+    /// no longer considers claimed counts as released. Interfaces that fail to release stay
+    /// tracked, so they are retried when the handle is dropped.
+    ///
+    /// ## Errors
+    ///
+    /// Every tracked interface is attempted; the first error encountered is returned.
+    pub fn release_all_interfaces(&mut self) -> crate::Result<()> {
+        let handle = self.as_raw();
+        let interfaces = self.interfaces.get_mut().unwrap();
+        let mut result = Ok(());
+
+        let claimed = *interfaces;
+        for iface in claimed.iter() {
+            match unsafe { libusb_release_interface(handle, c_int::from(iface)) } {
+                0 | LIBUSB_ERROR_NOT_FOUND => interfaces.remove(iface),
+                err => {
+                    if result.is_ok() {
+                        result = Err(error::from_libusb(err));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
     /// Sets an interface's active setting.
+    ///
+    /// Selecting a setting makes the host reserve bus bandwidth for its periodic endpoints. For
+    /// the isochronous and interrupt endpoints of e.g. video and audio class devices this can
+    /// fail when other devices on the bus already use most of the bandwidth, so it may succeed
+    /// or fail depending on what else is connected. Picking a setting with a smaller
+    /// `wMaxPacketSize`, or moving the device to another bus, usually helps.
+    ///
+    /// ## Errors
+    ///
+    /// `libusb` has no error code for a lack of bandwidth, and the backends report it
+    /// differently:
+    ///
+    /// * `Other` on Linux, where the kernel rejects the setting with `ENOSPC`. The `libusb` log
+    ///   shows the reason.
+    /// * Usually `Io` on other backends, which also covers a device that refuses the setting.
+    ///
+    /// The remaining errors are:
+    ///
+    /// * `NotFound` if the interface isn't claimed or the setting doesn't exist.
+    /// * `NoDevice` if the device has been disconnected.
     pub fn set_alternate_setting(&self, iface: u8, setting: u8) -> crate::Result<()> {
         try_unsafe!(libusb_set_interface_alt_setting(
             self.as_raw(),
@@ -321,6 +855,131 @@ impl<T: UsbContext> DeviceHandle<T> {
         Ok(())
     }
 
+    /// Reads an interface's active setting from the device with a `GET_INTERFACE` request.
+    pub fn alternate_setting(&self, iface: u8, timeout: Duration) -> crate::Result<u8> {
+        let mut buf = [0u8; 1];
+
+        let len = self.read_control(
+            request_type(Direction::In, RequestType::Standard, Recipient::Interface),
+            LIBUSB_REQUEST_GET_INTERFACE,
+            0,
+            interface_index(iface),
+            &mut buf,
+            timeout,
+        )?;
+
+        match len {
+            1 => Ok(buf[0]),
+            _ => Err(Error::Io),
+        }
+    }
+
+    /// Sets an interface's active setting and checks that the device applied it.
+    ///
+    /// Some devices silently ignore a change of alternate setting, for example when the bus
+    /// can't provide the bandwidth it needs. After setting it, this reads the active setting back
+    /// with [`alternate_setting`](#method.alternate_setting).
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Error::Other` if the device reports a setting other than `setting`. Otherwise
+    /// the errors are those of setting the interface or of the `GET_INTERFACE` request.
+    pub fn set_alternate_setting_verified(
+        &self,
+        iface: u8,
+        setting: u8,
+        timeout: Duration,
+    ) -> crate::Result<()> {
+        self.set_alternate_setting(iface, setting)?;
+
+        match self.alternate_setting(iface, timeout)? {
+            active if active == setting => Ok(()),
+            _ => Err(Error::Other),
+        }
+    }
+
+    /// Returns the addresses of the first bulk IN and the first bulk OUT endpoint of an
+    /// interface in the active configuration.
+    ///
+    /// The endpoints are looked up in the interface's first alternate setting, which is the one
+    /// selected after the configuration is set. For interfaces that move their endpoints to
+    /// another setting, walk the descriptors of
+    /// [`active_config_descriptor`](Device::active_config_descriptor) instead.
+    ///
+    /// ## Errors
+    ///
+    /// * `NotFound` if the active configuration has no interface `iface`.
+    /// * Any error of [`active_config_descriptor`](Device::active_config_descriptor).
+    pub fn bulk_endpoints(&self, iface: u8) -> crate::Result<(Option<u8>, Option<u8>)> {
+        let config = self.device().active_config_descriptor()?;
+
+        let setting = config
+            .interfaces()
+            .filter_map(|interface| interface.descriptors().next())
+            .find(|setting| setting.interface_number() == iface)
+            .ok_or(Error::NotFound)?;
+
+        let mut bulk_in = None;
+        let mut bulk_out = None;
+
+        for endpoint in setting.endpoint_descriptors() {
+            if endpoint.transfer_type() != TransferType::Bulk {
+                continue;
+            }
+
+            let slot = match endpoint.direction() {
+                Direction::In => &mut bulk_in,
+                Direction::Out => &mut bulk_out,
+            };
+            slot.get_or_insert(endpoint.address());
+        }
+
+        Ok((bulk_in, bulk_out))
+    }
+
+    /// Returns a good buffer size for bulk transfers on `endpoint`, in bytes.
+    ///
+    /// Each synchronous transfer costs at least one system call and, on Linux, one or more URBs
+    /// handed to the kernel, so reading a few packets at a time caps the throughput well below
+    /// what the bus can do. This suggests a buffer that holds roughly a millisecond of data at the
+    /// device's [speed](crate::Device::speed), from 4 KiB at full speed to 512 KiB at
+    /// SuperSpeed+.
+    ///
+    /// The size is a multiple of the endpoint's maximum packet size, so a read that fills the
+    /// buffer never ends in the middle of a packet, which `libusb` would report as `Overflow`.
+    /// If the endpoint can't be found in the active configuration, the size isn't rounded.
+    ///
+    /// Buffers are only a hint for the backend: `libusb` may still split a transfer into several
+    /// kernel requests, and the best size for a particular device can only be found by measuring.
+    #[doc(alias = "libusb_get_max_packet_size")]
+    pub fn optimal_bulk_buffer_size(&self, endpoint: u8) -> usize {
+        let device = unsafe { libusb_get_device(self.as_raw()) };
+        let max_packet_size = unsafe { libusb_get_max_packet_size(device, endpoint) };
+
+        // Negative values are errors, e.g. if the endpoint doesn't exist.
+        let max_packet_size = (max_packet_size > 0).then_some(max_packet_size as usize);
+
+        bulk_buffer_size(self.device().speed(), max_packet_size)
+    }
+
+    /// Allocates a buffer suitable for zero-copy transfers on this device.
+    ///
+    /// The memory is allocated by the kernel and mapped into the process, so transfers using it
+    /// avoid copying data between user and kernel buffers. The buffer is released when the
+    /// returned [`DmaBuffer`] is dropped.
+    ///
+    /// This method is only supported on Linux. On other platforms, or if the kernel is too old to
+    /// support it, `Error::NotSupported` is returned.
+    #[doc(alias = "libusb_dev_mem_alloc")]
+    pub fn alloc_dma_buffer(&self, len: usize) -> crate::Result<DmaBuffer<T>> {
+        let buffer = unsafe { libusb_dev_mem_alloc(self.as_raw(), len) };
+
+        match NonNull::new(buffer) {
+            Some(buffer) => Ok(unsafe { dma_buffer::from_libusb(self, buffer, len) }),
+            None => Err(Error::NotSupported),
+        }
+    }
+
     /// Reads from an interrupt endpoint.
     ///
     /// This function attempts to read from the interrupt endpoint with the address given by the
@@ -353,8 +1012,9 @@ impl<T: UsbContext> DeviceHandle<T> {
         if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
         let mut transferred = mem::MaybeUninit::<c_int>::uninit();
-        unsafe {
+        let result = unsafe {
             match libusb_interrupt_transfer(
                 self.as_raw(),
                 endpoint,
@@ -374,7 +1034,81 @@ impl<T: UsbContext> DeviceHandle<T> {
                 }
                 err => Err(error::from_libusb(err)),
             }
-        }
+        };
+        self.record_transfer(result)
+    }
+
+    /// Reads up to `max_len` bytes from an interrupt endpoint into a new vector.
+    ///
+    /// This is [`read_interrupt`](#method.read_interrupt) with a buffer allocated for the call.
+    /// The returned vector holds exactly the bytes that were received. The errors are the same
+    /// as for [`read_interrupt`](#method.read_interrupt).
+    pub fn read_interrupt_vec(
+        &self,
+        endpoint: u8,
+        max_len: usize,
+        timeout: Duration,
+    ) -> crate::Result<Vec<u8>> {
+        let mut buf = vec![0; max_len];
+        let len = self.read_interrupt(endpoint, &mut buf, timeout)?;
+        buf.truncate(len);
+
+        Ok(buf)
+    }
+
+    /// Reads from an interrupt endpoint, retrying after timeouts until `deadline`.
+    ///
+    /// Interrupt endpoints that only report user actions, like a button press, may stay silent
+    /// for a long time. This polls the endpoint with reads of at most `per_try_timeout` each, so
+    /// that a short poll interval doesn't limit how long the caller is willing to wait in total.
+    /// The last read is shortened so that the call returns by `deadline`.
+    ///
+    /// If the return value is `Ok(n)`, then `buf` is populated with `n` bytes of data received
+    /// from the endpoint.
+    ///
+    /// ## Errors
+    ///
+    /// * `Timeout` if no data arrived before `deadline`.
+    ///
+    /// Any other error of [`read_interrupt`](#method.read_interrupt) ends the polling and is
+    /// returned right away.
+    pub fn read_interrupt_poll(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        per_try_timeout: Duration,
+        deadline: Instant,
+    ) -> crate::Result<usize> {
+        poll_until(deadline, per_try_timeout, |timeout| {
+            self.read_interrupt(endpoint, buf, timeout)
+        })
+    }
+
+    /// Reads a batch of fixed-size reports from an interrupt endpoint.
+    ///
+    /// This function performs up to `count` interrupt reads of `report_size` bytes each from the
+    /// endpoint with the address given by the `endpoint` parameter, and returns the reports in the
+    /// order they were received. Each read blocks up to the amount of time specified by `timeout`.
+    ///
+    /// The batch ends early when a read times out or returns fewer than `report_size` bytes. A
+    /// short report is still part of the batch, so the returned vector may hold fewer than `count`
+    /// reports and its last report may be shorter than `report_size`.
+    ///
+    /// ## Errors
+    ///
+    /// A timeout only ends the batch. Any other error is returned, even after some reports were
+    /// read, and those reports are lost. See [`read_interrupt`](DeviceHandle::read_interrupt)
+    /// for the possible errors.
+    pub fn read_interrupt_multi(
+        &self,
+        endpoint: u8,
+        report_size: usize,
+        count: usize,
+        timeout: Duration,
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        read_reports(report_size, count, |buf| {
+            self.read_interrupt(endpoint, buf, timeout)
+        })
     }
 
     /// Writes to an interrupt endpoint.
@@ -407,8 +1141,9 @@ impl<T: UsbContext> DeviceHandle<T> {
         if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_OUT {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
         let mut transferred = mem::MaybeUninit::<c_int>::uninit();
-        unsafe {
+        let result = unsafe {
             match libusb_interrupt_transfer(
                 self.as_raw(),
                 endpoint,
@@ -428,7 +1163,8 @@ impl<T: UsbContext> DeviceHandle<T> {
                 }
                 err => Err(error::from_libusb(err)),
             }
-        }
+        };
+        self.record_transfer(result)
     }
 
     /// Reads from a bulk endpoint.
@@ -441,6 +1177,10 @@ impl<T: UsbContext> DeviceHandle<T> {
     /// If the return value is `Ok(n)`, then `buf` is populated with `n` bytes of data received
     /// from the endpoint.
     ///
+    /// Small buffers limit the throughput, since every call is at least one request to the
+    /// kernel. [`optimal_bulk_buffer_size`](#method.optimal_bulk_buffer_size) suggests a size
+    /// for sustained reads.
+    ///
     /// ## Errors
     ///
     /// If this function encounters any form of error while fulfilling the transfer request, an
@@ -463,8 +1203,9 @@ impl<T: UsbContext> DeviceHandle<T> {
         if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
         let mut transferred = mem::MaybeUninit::<c_int>::uninit();
-        unsafe {
+        let result = unsafe {
             match libusb_bulk_transfer(
                 self.as_raw(),
                 endpoint,
@@ -484,21 +1225,218 @@ impl<T: UsbContext> DeviceHandle<T> {
                 }
                 err => Err(error::from_libusb(err)),
             }
-        }
+        };
+        self.record_transfer(result)
     }
 
-    /// Writes to a bulk endpoint.
+    /// Reads from a bulk endpoint until at least `min_bytes` have arrived.
     ///
-    /// This function attempts to write the contents of `buf` to the bulk endpoint with the address
-    /// given by the `endpoint` parameter. The function blocks up to the amount of time specified
-    /// by `timeout`. Minimal `timeout` is 1 milliseconds, anything smaller will result in an
-    /// infinite block.
+    /// A single [`read_bulk`](#method.read_bulk) ends at the first short packet, which may leave
+    /// a message incomplete when the device splits it across transfers. This keeps reading into
+    /// the rest of `buf` until at least `min_bytes` have accumulated. Each read
+    /// still ends at a short packet, so the total may exceed `min_bytes` but never `buf.len()`.
     ///
-    /// If the return value is `Ok(n)`, then `n` bytes of `buf` were written to the endpoint.
+    /// `timeout` applies to the whole call rather than to each read. If it elapses, the bytes
+    /// read so far are returned, even if fewer than `min_bytes`. As for `read_bulk`, a `timeout`
+    /// of zero blocks until `min_bytes` have arrived.
     ///
     /// ## Errors
     ///
-    /// If this function encounters any form of error while fulfilling the transfer request, an
+    /// * `InvalidParam` if `min_bytes` is larger than `buf`.
+    /// * `Timeout` if the timeout elapsed before any data arrived.
+    ///
+    /// Otherwise the errors are the same as for [`read_bulk`](#method.read_bulk). An error is only
+    /// returned if no bytes were read; after a partial read it ends the call like a timeout.
+    pub fn read_bulk_at_least(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        min_bytes: usize,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        read_at_least(buf, min_bytes, timeout, |buf, timeout| {
+            self.read_bulk(endpoint, buf, timeout)
+        })
+    }
+
+    /// Reads from a bulk endpoint until data arrives or `cancel` is set.
+    ///
+    /// This behaves like [`read_bulk`](#method.read_bulk) without a timeout, except that the
+    /// read can be aborted from another thread by setting `cancel`. The transfer is submitted
+    /// asynchronously and `cancel` is checked every `poll_interval` while events are handled, so
+    /// the read ends at most about one `poll_interval` after `cancel` is set.
+    ///
+    /// If the return value is `Ok(n)`, then `buf` is populated with `n` bytes of data received
+    /// from the endpoint, which may be less than a full transfer if it was cancelled.
+    ///
+    /// ## Errors
+    ///
+    /// * `Interrupted` if the read was cancelled before any data arrived.
+    /// * `InvalidParam` if the endpoint is not an input endpoint.
+    ///
+    /// Otherwise the errors are the same as for [`read_bulk`](#method.read_bulk).
+    pub fn read_bulk_cancelable(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        cancel: &AtomicBool,
+        poll_interval: Duration,
+    ) -> crate::Result<usize> {
+        if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
+            return Err(Error::InvalidParam);
+        }
+
+        let _in_flight = self.in_flight();
+        let transfer = Transfer::bulk(
+            self.context.as_raw(),
+            self.as_raw(),
+            endpoint,
+            buf,
+            Duration::ZERO,
+        );
+        self.record_transfer(run_cancelable(transfer, cancel, poll_interval))
+    }
+
+    /// Reads from a bulk endpoint without waiting for data.
+    ///
+    /// A transfer is submitted and events are handled once without blocking. If the transfer
+    /// hasn't completed by then, it is cancelled, and any data that arrived in the meantime is
+    /// returned. This never waits for the device, but it does wait for the cancellation to be
+    /// acknowledged, which the kernel or host controller does promptly.
+    ///
+    /// Data only counts as available if the device had it ready and the host completed the
+    /// transfer before events were handled, so this suits polling loops that retry on
+    /// `Timeout`. A device that needs a moment to answer is better served by
+    /// [`read_bulk`](#method.read_bulk) with a short timeout.
+    ///
+    /// ## Errors
+    ///
+    /// * `Timeout` if no data was available.
+    /// * `InvalidParam` if the endpoint is not an input endpoint.
+    ///
+    /// Otherwise the errors are the same as for [`read_bulk`](#method.read_bulk).
+    pub fn try_read_bulk(&self, endpoint: u8, buf: &mut [u8]) -> crate::Result<usize> {
+        if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
+            return Err(Error::InvalidParam);
+        }
+
+        let _in_flight = self.in_flight();
+        let transfer = Transfer::bulk(
+            self.context.as_raw(),
+            self.as_raw(),
+            endpoint,
+            buf,
+            Duration::ZERO,
+        );
+        self.record_transfer(run_nonblocking(transfer))
+    }
+
+    /// Reads up to `length` bytes from a bulk endpoint with a single asynchronous transfer and
+    /// blocks until it ends.
+    ///
+    /// The transfer is submitted and events are handled until this transfer completes, so
+    /// events for other transfers of the context are processed along the way. The returned
+    /// vector holds exactly the bytes that were received. A transfer that times out or is
+    /// interrupted after receiving some data returns that data rather than an error.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidParam` if the endpoint is not an input endpoint.
+    /// * `Timeout` if the transfer timed out without receiving data.
+    ///
+    /// Otherwise the errors are the same as for [`read_bulk`](#method.read_bulk).
+    pub fn submit_and_wait(
+        &self,
+        endpoint: u8,
+        length: usize,
+        timeout: Duration,
+    ) -> crate::Result<Vec<u8>> {
+        if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
+            return Err(Error::InvalidParam);
+        }
+
+        let mut buf = vec![0; length];
+        let len = {
+            let _in_flight = self.in_flight();
+            let transfer = Transfer::bulk(
+                self.context.as_raw(),
+                self.as_raw(),
+                endpoint,
+                &mut buf,
+                timeout,
+            );
+            self.record_transfer(run_to_end(transfer))?
+        };
+        buf.truncate(len);
+
+        Ok(buf)
+    }
+
+    /// Reads from a bulk endpoint and reports the bytes received even if the read fails.
+    ///
+    /// This behaves like [`read_bulk`](#method.read_bulk), but the number of bytes placed in
+    /// `buf` is returned alongside the outcome of the transfer instead of being folded into it.
+    /// A read that times out or stalls after part of the data arrived returns that count with
+    /// the error, so a streaming protocol can keep the partial frame and resume.
+    ///
+    /// The errors are the same as for [`read_bulk`](#method.read_bulk), except that a timeout
+    /// is reported as `Timeout` even if data arrived.
+    pub fn read_bulk_partial(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> (usize, crate::Result<()>) {
+        if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
+            return (0, Err(Error::InvalidParam));
+        }
+
+        let _in_flight = self.in_flight();
+        self.record_partial_transfer(run_partial(Transfer::bulk(
+            self.context.as_raw(),
+            self.as_raw(),
+            endpoint,
+            buf,
+            timeout,
+        )))
+    }
+
+    /// Writes to a bulk endpoint and reports the bytes sent even if the write fails.
+    ///
+    /// This behaves like [`write_bulk`](#method.write_bulk), but the number of bytes of `buf`
+    /// that were written is returned alongside the outcome of the transfer, so the rest can be
+    /// sent again after a recoverable error.
+    ///
+    /// The errors are the same as for [`write_bulk`](#method.write_bulk), except that a
+    /// timeout is reported as `Timeout` even if data was sent.
+    pub fn write_bulk_partial(
+        &self,
+        endpoint: u8,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> (usize, crate::Result<()>) {
+        let _in_flight = self.in_flight();
+        self.record_partial_transfer(run_partial(Transfer::bulk_out(
+            self.context.as_raw(),
+            self.as_raw(),
+            endpoint,
+            buf,
+            timeout,
+        )))
+    }
+
+    /// Writes to a bulk endpoint.
+    ///
+    /// This function attempts to write the contents of `buf` to the bulk endpoint with the address
+    /// given by the `endpoint` parameter. The function blocks up to the amount of time specified
+    /// by `timeout`. Minimal `timeout` is 1 milliseconds, anything smaller will result in an
+    /// infinite block.
+    ///
+    /// If the return value is `Ok(n)`, then `n` bytes of `buf` were written to the endpoint.
+    ///
+    /// ## Errors
+    ///
+    /// If this function encounters any form of error while fulfilling the transfer request, an
     /// error variant will be returned. If an error variant is returned, no bytes were written.
     ///
     /// The errors returned by this function include:
@@ -512,8 +1450,9 @@ impl<T: UsbContext> DeviceHandle<T> {
         if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_OUT {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
         let mut transferred = mem::MaybeUninit::<c_int>::uninit();
-        unsafe {
+        let result = unsafe {
             match libusb_bulk_transfer(
                 self.as_raw(),
                 endpoint,
@@ -533,6 +1472,137 @@ impl<T: UsbContext> DeviceHandle<T> {
                 }
                 err => Err(error::from_libusb(err)),
             }
+        };
+        self.record_transfer(result)
+    }
+
+    /// Writes several buffers to a bulk endpoint, one transfer per buffer, in order.
+    ///
+    /// This avoids copying data that arrives in pieces, e.g. from a network socket, into one
+    /// contiguous buffer first. Each slice is sent with [`write_bulk`](#method.write_bulk), so a
+    /// slice whose length isn't a multiple of the endpoint's maximum packet size ends with a
+    /// short packet, which the device sees as the end of a transfer. Join the data into one
+    /// buffer instead if the protocol doesn't allow that.
+    ///
+    /// `timeout` applies to the whole call rather than to each slice, and a `timeout` of zero
+    /// blocks until everything was written. The writing stops at the first slice that isn't
+    /// written completely.
+    ///
+    /// If the return value is `Ok(n)`, then the first `n` bytes of the slices were written.
+    ///
+    /// ## Errors
+    ///
+    /// The errors are the same as for [`write_bulk`](#method.write_bulk). An error is only
+    /// returned if no bytes were written; after a partial write it ends the call like a timeout.
+    pub fn write_bulk_vectored(
+        &self,
+        endpoint: u8,
+        bufs: &[IoSlice<'_>],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        write_slices(bufs, timeout, |buf, timeout| {
+            self.write_bulk(endpoint, buf, timeout)
+        })
+    }
+
+    /// Writes the contents of `data` to a bulk endpoint.
+    ///
+    /// This is [`write_bulk`](#method.write_bulk) for data held in [`bytes::Bytes`], which is
+    /// written without being copied.
+    ///
+    /// **Note**: This method is only available with the `bytes` feature.
+    #[cfg(feature = "bytes")]
+    pub fn write_bulk_bytes(
+        &self,
+        endpoint: u8,
+        data: &bytes::Bytes,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        self.write_bulk(endpoint, data, timeout)
+    }
+
+    /// Writes a large buffer to a bulk endpoint in several transfers.
+    ///
+    /// A single bulk transfer can't be arbitrarily large: the operating system bounds how much
+    /// memory it will pin for one request and `libusb` reports `InvalidParam` when a transfer
+    /// exceeds it. On Linux, usbfs limits the memory of all in-flight transfers to 16 MiB by
+    /// default (the `usbfs_memory_mb` module parameter), and other backends impose limits of
+    /// their own. This function splits `buf` into pieces of at most 1 MiB and writes them one
+    /// after another with [`write_bulk`](#method.write_bulk), so that the size of `buf` is not
+    /// limited by the platform.
+    ///
+    /// `timeout` applies to each piece separately. The function stops early if a piece is only
+    /// partially written.
+    ///
+    /// If the return value is `Ok(n)`, then the first `n` bytes of `buf` were written to the
+    /// endpoint.
+    ///
+    /// ## Errors
+    ///
+    /// If a piece times out or is interrupted after earlier pieces were written, the number of
+    /// bytes written so far is returned instead of an error. Otherwise the errors are the same as
+    /// for [`write_bulk`](#method.write_bulk).
+    pub fn write_bulk_chunked(
+        &self,
+        endpoint: u8,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        if buf.is_empty() {
+            return self.write_bulk(endpoint, buf, timeout);
+        }
+
+        let mut written = 0;
+        for chunk in buf.chunks(MAX_BULK_CHUNK) {
+            match self.write_bulk(endpoint, chunk, timeout) {
+                Ok(n) => {
+                    written += n;
+                    if n < chunk.len() {
+                        break;
+                    }
+                }
+                Err(Error::Timeout | Error::Interrupted) if written > 0 => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Returns an iterator over fixed-size frames read from a bulk endpoint.
+    ///
+    /// Each item is one frame of exactly `frame_size` bytes. The iterator reads from `endpoint`
+    /// with [`read_bulk`](#method.read_bulk) in chunks spanning several frames, and carries an
+    /// incomplete frame over to the next read. `timeout` applies to each read. For best results
+    /// `frame_size` should be a multiple of the endpoint's maximum packet size, so that reads
+    /// never end in the middle of a packet.
+    ///
+    /// Read errors are yielded as `Err` items. The iterator yields nothing if `frame_size` is
+    /// zero.
+    pub fn bulk_frames(&self, endpoint: u8, frame_size: usize, timeout: Duration) -> BulkFrames {
+        BulkFrames::new(self, endpoint, frame_size, timeout)
+    }
+
+    /// Performs a bulk transfer in the direction of `endpoint`.
+    ///
+    /// This is a single entry point for [`read_bulk`](#method.read_bulk) and
+    /// [`write_bulk`](#method.write_bulk). The direction bit of the endpoint address selects
+    /// which one is performed, and `buf` must be the matching [`BufKind`] variant.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if the kind of `buf` does not match the direction of `endpoint`.
+    /// Otherwise the errors are the same as for the underlying read or write.
+    pub fn bulk_transfer(
+        &self,
+        endpoint: u8,
+        buf: BufKind,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        match (endpoint & LIBUSB_ENDPOINT_DIR_MASK, buf) {
+            (LIBUSB_ENDPOINT_IN, BufKind::In(buf)) => self.read_bulk(endpoint, buf, timeout),
+            (LIBUSB_ENDPOINT_OUT, BufKind::Out(buf)) => self.write_bulk(endpoint, buf, timeout),
+            _ => Err(Error::InvalidParam),
         }
     }
 
@@ -541,7 +1611,9 @@ impl<T: UsbContext> DeviceHandle<T> {
     /// This function attempts to read data from the device using a control transfer and fills
     /// `buf` with any data received during the transfer. The function blocks up to the amount of
     /// time specified by `timeout`. Minimal `timeout` is 1 milliseconds, anything smaller will
-    /// result in an infinite block.
+    /// result in an infinite block. In particular, `Duration::ZERO` waits forever rather than
+    /// returning immediately; use [`control_in_blocking`](#method.control_in_blocking) to make an
+    /// infinite wait explicit.
     ///
     /// The parameters `request_type`, `request`, `value`, and `index` specify the fields of the
     /// control transfer setup packet (`bmRequestType`, `bRequest`, `wValue`, and `wIndex`
@@ -576,6 +1648,7 @@ impl<T: UsbContext> DeviceHandle<T> {
         if request_type & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
         let res = unsafe {
             libusb_control_transfer(
                 self.as_raw(),
@@ -589,11 +1662,11 @@ impl<T: UsbContext> DeviceHandle<T> {
             )
         };
 
-        if res < 0 {
+        self.record_transfer(if res < 0 {
             Err(error::from_libusb(res))
         } else {
             Ok(res as usize)
-        }
+        })
     }
 
     /// Writes data using a control transfer.
@@ -601,6 +1674,8 @@ impl<T: UsbContext> DeviceHandle<T> {
     /// This function attempts to write the contents of `buf` to the device using a control
     /// transfer. The function blocks up to the amount of time specified by `timeout`.
     /// Minimal `timeout` is 1 milliseconds, anything smaller will result in an infinite block.
+    /// In particular, `Duration::ZERO` waits forever rather than returning immediately; use
+    /// [`control_out_blocking`](#method.control_out_blocking) to make an infinite wait explicit.
     ///
     /// The parameters `request_type`, `request`, `value`, and `index` specify the fields of the
     /// control transfer setup packet (`bmRequestType`, `bRequest`, `wValue`, and `wIndex`
@@ -635,24 +1710,389 @@ impl<T: UsbContext> DeviceHandle<T> {
         if request_type & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_OUT {
             return Err(Error::InvalidParam);
         }
+        let _in_flight = self.in_flight();
+        let res = unsafe {
+            libusb_control_transfer(
+                self.as_raw(),
+                request_type,
+                request,
+                value,
+                index,
+                buf.as_ptr() as *mut c_uchar,
+                buf.len() as u16,
+                timeout.as_millis() as c_uint,
+            )
+        };
+
+        self.record_transfer(if res < 0 {
+            Err(error::from_libusb(res))
+        } else {
+            Ok(res as usize)
+        })
+    }
+
+    /// Reads data using a control transfer, waiting for it to end without a timeout.
+    ///
+    /// This is [`read_control`](#method.read_control) with an infinite timeout. It only returns
+    /// once the device completes the request, fails it, or is disconnected.
+    pub fn control_in_blocking(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &mut [u8],
+    ) -> crate::Result<usize> {
+        self.read_control(request_type, request, value, index, buf, Duration::ZERO)
+    }
+
+    /// Writes data using a control transfer, waiting for it to end without a timeout.
+    ///
+    /// This is [`write_control`](#method.write_control) with an infinite timeout. It only
+    /// returns once the device completes the request, fails it, or is disconnected.
+    pub fn control_out_blocking(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &[u8],
+    ) -> crate::Result<usize> {
+        self.write_control(request_type, request, value, index, buf, Duration::ZERO)
+    }
+
+    /// Reads data using a control transfer addressed to the interface `iface`.
+    ///
+    /// The request type is built from `kind` with the recipient set to the interface, and the
+    /// interface number is sent in `wIndex` as the USB specification requires. This avoids the
+    /// common mistakes of addressing an interface request to the device or leaving the interface
+    /// number out of `wIndex`. Use [`read_control`](#method.read_control) for requests that
+    /// need the high byte of `wIndex`.
+    ///
+    /// The errors are the same as for [`read_control`](#method.read_control).
+    pub fn control_in_interface(
+        &self,
+        iface: u8,
+        kind: RequestType,
+        request: u8,
+        value: u16,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        self.read_control(
+            request_type(Direction::In, kind, Recipient::Interface),
+            request,
+            value,
+            interface_index(iface),
+            buf,
+            timeout,
+        )
+    }
+
+    /// Writes data using a control transfer addressed to the interface `iface`.
+    ///
+    /// See [`control_in_interface`](#method.control_in_interface). The errors are the same as
+    /// for [`write_control`](#method.write_control).
+    pub fn control_out_interface(
+        &self,
+        iface: u8,
+        kind: RequestType,
+        request: u8,
+        value: u16,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        self.write_control(
+            request_type(Direction::Out, kind, Recipient::Interface),
+            request,
+            value,
+            interface_index(iface),
+            buf,
+            timeout,
+        )
+    }
+
+    /// Reads data using a control transfer addressed to the endpoint `endpoint`.
+    ///
+    /// The request type is built from `kind` with the recipient set to the endpoint, and
+    /// `endpoint` is sent in `wIndex`. It is the endpoint address including the direction bit,
+    /// e.g. `0x81` for endpoint 1 IN, as the USB specification requires.
+    ///
+    /// The errors are the same as for [`read_control`](#method.read_control).
+    pub fn control_in_endpoint(
+        &self,
+        endpoint: u8,
+        kind: RequestType,
+        request: u8,
+        value: u16,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        self.read_control(
+            request_type(Direction::In, kind, Recipient::Endpoint),
+            request,
+            value,
+            u16::from(endpoint),
+            buf,
+            timeout,
+        )
+    }
+
+    /// Writes data using a control transfer addressed to the endpoint `endpoint`.
+    ///
+    /// See [`control_in_endpoint`](#method.control_in_endpoint). The errors are the same as for
+    /// [`write_control`](#method.write_control).
+    pub fn control_out_endpoint(
+        &self,
+        endpoint: u8,
+        kind: RequestType,
+        request: u8,
+        value: u16,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        self.write_control(
+            request_type(Direction::Out, kind, Recipient::Endpoint),
+            request,
+            value,
+            u16::from(endpoint),
+            buf,
+            timeout,
+        )
+    }
+
+    /// Writes `data` with a series of control transfers of at most `block_size` bytes each,
+    /// numbering the blocks in `wValue`.
+    ///
+    /// `wLength` is 16 bits wide, so a single control transfer carries at most 65535 bytes.
+    /// Firmware downloads, such as DFU's `DFU_DNLOAD`, send larger images as consecutive blocks
+    /// with the block number in `wValue`. The first block is sent with `wValue` set to
+    /// `start_value` and each following block with the next number, wrapping around after
+    /// 65535. The last block may be shorter than `block_size`; protocols that end a download
+    /// with an empty block expect the caller to send it.
+    ///
+    /// `timeout` applies to each block separately. The function stops early if a block is only
+    /// partially written. If the return value is `Ok(n)`, then the first `n` bytes of `data`
+    /// were written.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if `request_type` is not a host-to-device request or `block_size`
+    /// is 0 or larger than 65535. If a block times out or is interrupted after earlier blocks
+    /// were written, the number of bytes written so far is returned instead of an error.
+    /// Otherwise the errors are the same as for [`write_control`](#method.write_control).
+    #[allow(clippy::too_many_arguments)]
+    pub fn control_out_blocks(
+        &self,
+        request_type: u8,
+        request: u8,
+        start_value: u16,
+        index: u16,
+        data: &[u8],
+        block_size: usize,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        if request_type & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_OUT {
+            return Err(Error::InvalidParam);
+        }
+
+        write_blocks(data, block_size, start_value, |value, block| {
+            self.write_control(request_type, request, value, index, block, timeout)
+        })
+    }
+
+    /// Performs a control transfer described by an explicit setup packet.
+    ///
+    /// The data stage is exactly `setup.length` bytes long, regardless of the length of `buf`.
+    /// For a device-to-host request `buf` must be [`BufKind::In`] and is filled from the start;
+    /// for a host-to-device request it must be [`BufKind::Out`] and its first `setup.length`
+    /// bytes are sent. This guards against sending a `wLength` that silently follows an
+    /// oversized buffer instead of what the protocol expects.
+    ///
+    /// If the return value is `Ok(n)`, then `n` bytes were transferred in the data stage.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if `buf` is shorter than `setup.length` or if the kind of `buf`
+    /// does not match the direction of `setup.request_type`. Otherwise the errors are the same as
+    /// for [`read_control`](#method.read_control) and [`write_control`](#method.write_control).
+    pub fn control_transfer(
+        &self,
+        setup: ControlSetup,
+        buf: BufKind,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        let length = usize::from(setup.length);
+
+        match (setup.request_type & LIBUSB_ENDPOINT_DIR_MASK, buf) {
+            (LIBUSB_ENDPOINT_IN, BufKind::In(buf)) if buf.len() >= length => self.read_control(
+                setup.request_type,
+                setup.request,
+                setup.value,
+                setup.index,
+                &mut buf[..length],
+                timeout,
+            ),
+            (LIBUSB_ENDPOINT_OUT, BufKind::Out(buf)) if buf.len() >= length => self.write_control(
+                setup.request_type,
+                setup.request,
+                setup.value,
+                setup.index,
+                &buf[..length],
+                timeout,
+            ),
+            _ => Err(Error::InvalidParam),
+        }
+    }
+
+    /// Reads data using a control transfer that requests at most `wlength` bytes.
+    ///
+    /// [`read_control`](#method.read_control) sends the length of `buf` as `wLength`. Some
+    /// protocols expect a particular `wLength`, which caps how much the device may answer,
+    /// while the caller's buffer is larger. This sends `wlength` instead and reads into the
+    /// beginning of `buf`; `setup.length` is ignored. It is equivalent to
+    /// [`control_transfer`](#method.control_transfer) with `setup.length` set to `wlength`.
+    ///
+    /// If the return value is `Ok(n)`, then the first `n` bytes of `buf` were received, where
+    /// `n` is at most `wlength`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if `wlength` is larger than `buf` or if `setup.request_type` does
+    /// not specify a read transfer. Otherwise the errors are the same as for
+    /// [`read_control`](#method.read_control).
+    pub fn control_in_limited(
+        &self,
+        setup: ControlSetup,
+        buf: &mut [u8],
+        wlength: u16,
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        let setup = ControlSetup {
+            length: wlength,
+            ..setup
+        };
+
+        self.control_transfer(setup, BufKind::In(buf), timeout)
+    }
+
+    /// Reads a variable-length response with a header read followed by a full read.
+    ///
+    /// This is the usual way to fetch descriptors such as the configuration or BOS descriptor,
+    /// whose total length is only known from their header: the request described by `setup` is
+    /// first sent with `wLength` set to `setup.length`, the total length is taken from the
+    /// little-endian `u16` at `length_offset` of that response, and the request is sent again
+    /// with `wLength` set to the total length. Returns the response to the second request,
+    /// which may be shorter than announced. `timeout` applies to each of the two reads.
+    ///
+    /// For a configuration descriptor, `setup.length` would be 9 and `length_offset` 2, the
+    /// offset of `wTotalLength`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if `setup` is not an IN request or if the length field doesn't fit
+    /// in `setup.length` bytes, and `MalformedDescriptor` if the first response ends before the
+    /// length field. Otherwise the errors are the same as for
+    /// [`read_control`](#method.read_control).
+    pub fn read_control_sized(
+        &self,
+        setup: ControlSetup,
+        length_offset: usize,
+        timeout: Duration,
+    ) -> crate::Result<Vec<u8>> {
+        if setup.request_type & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_IN {
+            return Err(Error::InvalidParam);
+        }
+
+        read_sized(usize::from(setup.length), length_offset, |buf| {
+            self.read_control(
+                setup.request_type,
+                setup.request,
+                setup.value,
+                setup.index,
+                buf,
+                timeout,
+            )
+        })
+    }
+
+    /// Performs a control transfer without a data stage.
+    ///
+    /// The setup packet is sent with a `wLength` of zero and the transfer completes with the
+    /// status stage, which suits commands that carry all their arguments in `wValue` and
+    /// `wIndex`. The direction bit of `setup.request_type` is sent as given; it has no effect on
+    /// a transfer without data.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `InvalidParam` if `setup.length` is not zero. Otherwise the errors are the same
+    /// as for [`write_control`](#method.write_control), with `Pipe` meaning the device rejected
+    /// the request.
+    pub fn control_no_data(&self, setup: ControlSetup, timeout: Duration) -> crate::Result<()> {
+        if setup.length != 0 {
+            return Err(Error::InvalidParam);
+        }
+
+        let _in_flight = self.in_flight();
         let res = unsafe {
             libusb_control_transfer(
                 self.as_raw(),
-                request_type,
-                request,
-                value,
-                index,
-                buf.as_ptr() as *mut c_uchar,
-                buf.len() as u16,
+                setup.request_type,
+                setup.request,
+                setup.value,
+                setup.index,
+                ptr::null_mut(),
+                0,
                 timeout.as_millis() as c_uint,
             )
         };
 
-        if res < 0 {
+        self.record_transfer(if res < 0 {
             Err(error::from_libusb(res))
         } else {
-            Ok(res as usize)
+            Ok(0)
+        })?;
+        Ok(())
+    }
+
+    /// Performs a sequence of control transfers in order, stopping at the first failure.
+    ///
+    /// This suits devices that are initialized with a table of control requests. Each entry is
+    /// performed with [`control_transfer`](#method.control_transfer). For host-to-device
+    /// requests the data stage is taken from the entry's data. For device-to-host requests the
+    /// entry's data is ignored and the `length` bytes that are read are discarded. A write that
+    /// transfers fewer than `length` bytes counts as failed with `Error::Io`.
+    ///
+    /// `timeout` applies to each transfer.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`SequenceError`] holding the index of the transfer that failed and its error.
+    /// The transfers after it are not performed.
+    pub fn control_sequence(
+        &self,
+        setups: &[(ControlSetup, Vec<u8>)],
+        timeout: Duration,
+    ) -> Result<(), SequenceError> {
+        let mut scratch = Vec::new();
+
+        for (index, (setup, data)) in setups.iter().enumerate() {
+            let result = if setup.request_type & LIBUSB_ENDPOINT_DIR_MASK == LIBUSB_ENDPOINT_IN {
+                scratch.resize(usize::from(setup.length), 0);
+                self.control_transfer(*setup, BufKind::In(&mut scratch), timeout)
+                    .map(|_| ())
+            } else {
+                match self.control_transfer(*setup, BufKind::Out(data), timeout) {
+                    Ok(n) if n < usize::from(setup.length) => Err(Error::Io),
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e),
+                }
+            };
+
+            result.map_err(|error| SequenceError { index, error })?;
         }
+
+        Ok(())
     }
 
     /// Reads the languages supported by the device's string descriptors.
@@ -665,15 +2105,13 @@ impl<T: UsbContext> DeviceHandle<T> {
         let len = self.read_control(
             request_type(Direction::In, RequestType::Standard, Recipient::Device),
             LIBUSB_REQUEST_GET_DESCRIPTOR,
-            u16::from(LIBUSB_DT_STRING) << 8,
+            descriptor_value(LIBUSB_DT_STRING, 0),
             0,
             &mut buf,
             timeout,
         )?;
 
-        if len < 2 || buf[0] != len as u8 || len & 0x01 != 0 {
-            return Err(Error::BadDescriptor);
-        }
+        check_string_descriptor(&buf[..len])?;
 
         if len == 2 {
             return Ok(Vec::new());
@@ -682,33 +2120,88 @@ impl<T: UsbContext> DeviceHandle<T> {
         Ok(buf[0..len]
             .chunks(2)
             .skip(1)
-            .map(|chunk| {
-                let lang_id = u16::from(chunk[0]) | u16::from(chunk[1]) << 8;
-                crate::language::from_lang_id(lang_id)
-            })
+            .map(|chunk| Language::from_lang_id(fields::le_u16(chunk)))
             .collect())
     }
 
+    /// Reads the supported languages and picks the one best suited for showing strings to
+    /// people.
+    ///
+    /// English (United States) is preferred, then any other English dialect, and otherwise the
+    /// first language the device reports. This is a better default than the first language
+    /// alone, since devices don't always list English first even when they support it. Returns
+    /// `None` if the device supports no languages, i.e. has no string descriptors.
+    ///
+    /// The result can be passed to [`read_product_string`](#method.read_product_string) and
+    /// the other string reading functions.
+    ///
+    /// ## Errors
+    ///
+    /// The errors are the same as for [`read_languages`](#method.read_languages).
+    pub fn preferred_language(&self, timeout: Duration) -> crate::Result<Option<Language>> {
+        Ok(preferred_language(&self.read_languages(timeout)?))
+    }
+
     /// Reads a ascii string descriptor from the device.
     ///
+    /// The string is read in the first language the device reports, and every character outside
+    /// of ASCII is replaced with `?`. Use [`read_string_descriptor`](#method.read_string_descriptor)
+    /// to get the string unchanged.
+    ///
+    /// The string is never truncated. A string descriptor is at most 255 bytes long, since its
+    /// length is stored in a single byte, which leaves room for 126 characters; the 255-byte
+    /// buffer used here always holds them. A device that reports a longer descriptor than it
+    /// sends makes this fail with `Io` instead of returning a clipped string.
     pub fn read_string_descriptor_ascii(&self, index: u8) -> crate::Result<String> {
-        let mut buf = Vec::<u8>::with_capacity(255);
-
-        let ptr = buf.as_mut_ptr() as *mut c_uchar;
-        let capacity = buf.capacity() as i32;
+        // An initialized buffer, so that only the returned length has to be trusted, and checked,
+        // rather than libusb having written every byte that ends up in the string.
+        let mut buf = [0u8; 255];
 
-        let res =
-            unsafe { libusb_get_string_descriptor_ascii(self.as_raw(), index, ptr, capacity) };
+        let _in_flight = self.in_flight();
+        let res = unsafe {
+            libusb_get_string_descriptor_ascii(
+                self.as_raw(),
+                index,
+                buf.as_mut_ptr() as *mut c_uchar,
+                buf.len() as c_int,
+            )
+        };
 
         if res < 0 {
             return Err(error::from_libusb(res));
         }
 
-        unsafe {
-            buf.set_len(res as usize);
-        }
+        let string = buf.get(..res as usize).ok_or(Error::Io)?;
+        String::from_utf8(string.to_vec()).map_err(|_| Error::Other)
+    }
+
+    /// Reads the raw string descriptor `index` into `buf` and returns the number of bytes read.
+    ///
+    /// The request asks for `buf.len()` bytes, capped at the 65535 bytes a control transfer can
+    /// carry, and the descriptor is returned as sent by the device, including its two-byte
+    /// header. Nothing is validated, which makes this suitable for tools that dump descriptors.
+    /// Use [`read_string_descriptor`](#method.read_string_descriptor) to decode a string.
+    ///
+    /// Index 0 returns the descriptor listing the supported languages, for which `language` is
+    /// ignored by devices.
+    #[doc(alias = "libusb_get_string_descriptor")]
+    pub fn get_string_descriptor(
+        &self,
+        language: Language,
+        index: u8,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        let len = buf.len().min(usize::from(u16::MAX));
 
-        String::from_utf8(buf).map_err(|_| Error::Other)
+        self.read_control(
+            request_type(Direction::In, RequestType::Standard, Recipient::Device),
+            LIBUSB_REQUEST_GET_DESCRIPTOR,
+            descriptor_value(LIBUSB_DT_STRING, index),
+            language.lang_id(),
+            &mut buf[..len],
+            timeout,
+        )
     }
 
     /// Reads a string descriptor from the device.
@@ -736,15 +2229,13 @@ impl<T: UsbContext> DeviceHandle<T> {
             let len = self.read_control(
                 request_type(Direction::In, RequestType::Standard, Recipient::Device),
                 LIBUSB_REQUEST_GET_DESCRIPTOR,
-                u16::from(LIBUSB_DT_STRING) << 8 | u16::from(index),
+                descriptor_value(LIBUSB_DT_STRING, index),
                 language.lang_id(),
                 buf,
                 timeout,
             )?;
 
-            if len < 2 || buf[0] != len as u8 || len & 0x01 != 0 {
-                return Err(Error::BadDescriptor);
-            }
+            check_string_descriptor(&buf[..len])?;
 
             len
         };
@@ -851,12 +2342,508 @@ impl<T: UsbContext> DeviceHandle<T> {
             Some(n) => self.read_string_descriptor(language, n, timeout),
         }
     }
+
+    /// Reads the configuration descriptor `index` from the device, with all of its interface,
+    /// endpoint and class-specific descriptors.
+    ///
+    /// Unlike [`Device::config_descriptor`], which returns the descriptors libusb read when the
+    /// device was enumerated, this asks the device with `GET_DESCRIPTOR` requests: one for the
+    /// 9-byte header to learn `wTotalLength`, then one for the whole configuration. This sees
+    /// descriptors that changed without the device re-enumerating, e.g. after a firmware update.
+    ///
+    /// `index` is the position of the configuration, from 0 to `bNumConfigurations - 1`, not its
+    /// `bConfigurationValue`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `MalformedDescriptor` if the header is truncated or is not a configuration
+    /// descriptor, and the errors of [`read_control_sized`](#method.read_control_sized)
+    /// otherwise.
+    pub fn read_config_descriptor_raw(
+        &self,
+        index: u8,
+        timeout: Duration,
+    ) -> crate::Result<Vec<u8>> {
+        let setup = ControlSetup {
+            request_type: request_type(Direction::In, RequestType::Standard, Recipient::Device),
+            request: LIBUSB_REQUEST_GET_DESCRIPTOR,
+            value: descriptor_value(LIBUSB_DT_CONFIG, index),
+            index: 0,
+            length: u16::from(LIBUSB_DT_CONFIG_SIZE),
+        };
+        // `wTotalLength` follows `bLength` and `bDescriptorType`
+        let config = self.read_control_sized(setup, 2, timeout)?;
+        if config.get(1) != Some(&LIBUSB_DT_CONFIG) {
+            return Err(Error::MalformedDescriptor {
+                offset: 1,
+                reason: reason::NOT_CONFIG,
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the device's Microsoft OS 2.0 descriptor set.
+    ///
+    /// Windows uses these descriptors, among other things, to bind WinUSB to a device without an
+    /// INF file. They are announced by a platform capability in the device's BOS descriptor,
+    /// which names the vendor request that returns the descriptor set. Returns `Ok(None)` if the
+    /// device has no BOS descriptor or announces no MS OS 2.0 descriptors.
+    pub fn read_msos2_descriptor(&self, timeout: Duration) -> crate::Result<Option<Vec<u8>>> {
+        let setup = ControlSetup {
+            request_type: request_type(Direction::In, RequestType::Standard, Recipient::Device),
+            request: LIBUSB_REQUEST_GET_DESCRIPTOR,
+            value: descriptor_value(LIBUSB_DT_BOS, 0),
+            index: 0,
+            length: u16::from(LIBUSB_DT_BOS_SIZE),
+        };
+        // `wTotalLength` follows `bLength` and `bDescriptorType`
+        let bos = match self.read_control_sized(setup, 2, timeout) {
+            Ok(bos) => bos,
+            // devices before USB 2.1 stall the request for a BOS descriptor
+            Err(Error::Pipe) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if bos.get(1) != Some(&LIBUSB_DT_BOS) {
+            return Err(Error::MalformedDescriptor {
+                offset: 1,
+                reason: reason::NOT_BOS,
+            });
+        }
+
+        let (vendor_code, length) = match msos2_descriptor_set(&bos) {
+            Some(set) => set,
+            None => return Ok(None),
+        };
+
+        let mut buf = vec![0u8; usize::from(length)];
+        let len = self.read_control(
+            request_type(Direction::In, RequestType::Vendor, Recipient::Device),
+            vendor_code,
+            0,
+            MS_OS_20_DESCRIPTOR_INDEX,
+            &mut buf,
+            timeout,
+        )?;
+        buf.truncate(len);
+
+        Ok(Some(buf))
+    }
+}
+
+/// `PlatformCapabilityUUID` of the MS OS 2.0 platform capability,
+/// {D8DD60DF-4589-4CC7-9CD2-659D9E648A9F}, in the byte order used on the wire.
+const MS_OS_20_PLATFORM_UUID: [u8; 16] = [
+    0xDF, 0x60, 0xDD, 0xD8, 0x89, 0x45, 0xC7, 0x4C, 0x9C, 0xD2, 0x65, 0x9D, 0x9E, 0x64, 0x8A, 0x9F,
+];
+
+/// `wIndex` of the vendor request that returns the MS OS 2.0 descriptor set.
+const MS_OS_20_DESCRIPTOR_INDEX: u16 = 0x07;
+
+/// Feature selector of the standard `DEVICE_REMOTE_WAKEUP` feature.
+const DEVICE_REMOTE_WAKEUP: u16 = 0x01;
+
+/// Feature selector of the standard `TEST_MODE` feature.
+const TEST_MODE: u16 = 0x02;
+
+/// Feature selector of the hub class `PORT_TEST` feature.
+const PORT_TEST: u16 = 21;
+
+/// Device capability type of a platform capability descriptor.
+const PLATFORM_CAPABILITY: u8 = 0x05;
+
+/// Finds the MS OS 2.0 platform capability in a BOS descriptor and returns the vendor code and
+/// total length of its first descriptor set.
+fn msos2_descriptor_set(bos: &[u8]) -> Option<(u8, u16)> {
+    let mut capabilities = bos.get(usize::from(*bos.first()?)..)?;
+
+    while capabilities.len() >= 2 {
+        let len = usize::from(capabilities[0]);
+        if len < 2 || len > capabilities.len() {
+            return None;
+        }
+        let (capability, rest) = capabilities.split_at(len);
+
+        // bLength, bDescriptorType, bDevCapabilityType, bReserved, PlatformCapabilityUUID,
+        // then dwWindowsVersion, wMSOSDescriptorSetTotalLength, bMS_VendorCode, bAltEnumCode
+        if capability.len() >= 28
+            && capability[1] == LIBUSB_DT_DEVICE_CAPABILITY
+            && capability[2] == PLATFORM_CAPABILITY
+            && capability[4..20] == MS_OS_20_PLATFORM_UUID
+        {
+            let length = fields::le_u16(&capability[24..]);
+            return Some((capability[26], length));
+        }
+
+        capabilities = rest;
+    }
+
+    None
+}
+
+/// Runs `transfer` to its end and returns its result.
+fn run_to_end(transfer: crate::Result<Transfer>) -> crate::Result<usize> {
+    let mut transfer = transfer?;
+    transfer.submit()?;
+    transfer.wait()?;
+    transfer.result()
+}
+
+/// Runs `transfer` until it ends, cancelling it once `cancel` is set, which is checked every
+/// `poll_interval`.
+fn run_cancelable(
+    transfer: crate::Result<Transfer>,
+    cancel: &AtomicBool,
+    poll_interval: Duration,
+) -> crate::Result<usize> {
+    let mut transfer = transfer?;
+    transfer.submit()?;
+
+    while !transfer.poll(poll_interval)? {
+        if cancel.load(Ordering::Relaxed) {
+            transfer.cancel();
+            transfer.wait()?;
+        }
+    }
+
+    transfer.result()
+}
+
+/// Handles events for `transfer` once without blocking and cancels it if it hasn't ended by
+/// then, which is reported as `Timeout` unless data arrived.
+fn run_nonblocking(transfer: crate::Result<Transfer>) -> crate::Result<usize> {
+    let mut transfer = transfer?;
+    transfer.submit()?;
+
+    if !transfer.poll(Duration::ZERO)? {
+        transfer.cancel();
+        transfer.wait()?;
+    }
+
+    match transfer.result() {
+        Err(Error::Interrupted) => Err(Error::Timeout),
+        result => result,
+    }
+}
+
+/// Runs `transfer` to its end and returns the number of bytes transferred together with its
+/// status.
+fn run_partial(transfer: crate::Result<Transfer>) -> (usize, crate::Result<()>) {
+    let mut transfer = match transfer {
+        Ok(transfer) => transfer,
+        Err(e) => return (0, Err(e)),
+    };
+    if let Err(e) = transfer.submit() {
+        return (0, Err(e));
+    }
+
+    let result = transfer.wait().and_then(|()| transfer.status());
+    if transfer.is_completed() {
+        (transfer.actual_length(), result)
+    } else {
+        (0, result)
+    }
+}
+
+/// Picks a bulk buffer size for `speed`. See [`DeviceHandle::optimal_bulk_buffer_size`].
+fn bulk_buffer_size(speed: Speed, max_packet_size: Option<usize>) -> usize {
+    let size = match speed {
+        Speed::Low | Speed::Full => 4 * 1024,
+        Speed::High => 64 * 1024,
+        Speed::Super => 256 * 1024,
+        Speed::SuperPlus => 512 * 1024,
+        _ => 16 * 1024,
+    };
+
+    match max_packet_size {
+        Some(packet) => (size / packet).max(1) * packet,
+        None => size,
+    }
+}
+
+/// Returns when a call with an overall `timeout` has to end, or `None` if it may block forever
+/// because `timeout` is zero.
+fn deadline_after(timeout: Duration) -> Option<Instant> {
+    match timeout {
+        Duration::ZERO => None,
+        timeout => Some(Instant::now() + timeout),
+    }
+}
+
+/// Returns the timeout for the next transfer before `deadline`, or `None` once it has passed.
+fn time_left(deadline: Option<Instant>) -> Option<Duration> {
+    match deadline {
+        None => Some(Duration::ZERO),
+        // Anything below a millisecond would be passed to libusb as an infinite timeout.
+        Some(deadline) => deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| *remaining >= Duration::from_millis(1)),
+    }
+}
+
+/// Calls `read` with timeouts of at most `per_try` until it returns something other than
+/// `Timeout` or `deadline` passes. See [`DeviceHandle::read_interrupt_poll`].
+fn poll_until<F>(deadline: Instant, per_try: Duration, mut read: F) -> crate::Result<usize>
+where
+    F: FnMut(Duration) -> crate::Result<usize>,
+{
+    loop {
+        let timeout = match time_left(Some(deadline)) {
+            Some(remaining) => remaining.min(per_try),
+            None => return Err(Error::Timeout),
+        };
+
+        match read(timeout) {
+            Err(Error::Timeout) => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Fills `buf` from `read` until it holds at least `min_bytes` or `timeout` elapses. See
+/// [`DeviceHandle::read_bulk_at_least`].
+fn read_at_least<F>(
+    buf: &mut [u8],
+    min_bytes: usize,
+    timeout: Duration,
+    mut read: F,
+) -> crate::Result<usize>
+where
+    F: FnMut(&mut [u8], Duration) -> crate::Result<usize>,
+{
+    if min_bytes > buf.len() {
+        return Err(Error::InvalidParam);
+    }
+
+    let deadline = deadline_after(timeout);
+    let mut total = 0;
+
+    while total < min_bytes {
+        let remaining = match time_left(deadline) {
+            Some(remaining) => remaining,
+            None if total > 0 => break,
+            None => return Err(Error::Timeout),
+        };
+
+        match read(&mut buf[total..], remaining) {
+            Ok(n) => total += n,
+            Err(_) if total > 0 => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(total)
+}
+
+/// Picks a language from `languages`. See [`DeviceHandle::preferred_language`].
+fn preferred_language(languages: &[Language]) -> Option<Language> {
+    const ENGLISH_US: u16 = 0x0409;
+
+    languages
+        .iter()
+        .find(|language| language.lang_id() == ENGLISH_US)
+        .or_else(|| {
+            languages
+                .iter()
+                .find(|language| language.primary_language() == PrimaryLanguage::English)
+        })
+        .or_else(|| languages.first())
+        .copied()
+}
+
+/// Builds the setup packet of [`DeviceHandle::set_test_mode`].
+fn test_mode_setup(test_selector: u8, port: u8) -> ControlSetup {
+    let (kind, recipient, value) = match port {
+        0 => (RequestType::Standard, Recipient::Device, TEST_MODE),
+        _ => (RequestType::Class, Recipient::Other, PORT_TEST),
+    };
+
+    ControlSetup {
+        request_type: request_type(Direction::Out, kind, recipient),
+        request: LIBUSB_REQUEST_SET_FEATURE,
+        value,
+        index: u16::from(test_selector) << 8 | u16::from(port),
+        length: 0,
+    }
+}
+
+/// Checks that `descriptor`, the whole response to a string descriptor request, has a `bLength`
+/// matching its length and holds whole UTF-16 code units.
+fn check_string_descriptor(descriptor: &[u8]) -> crate::Result<()> {
+    let len = descriptor.len();
+    if len < 2 || usize::from(descriptor[0]) != len || len % 2 != 0 {
+        return Err(Error::MalformedDescriptor {
+            offset: 0,
+            reason: reason::STRING_LENGTH,
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads a header of `header_len` bytes with `read`, then as many bytes as the `u16` at
+/// `length_offset` of the header announces. See [`DeviceHandle::read_control_sized`].
+fn read_sized<F>(header_len: usize, length_offset: usize, mut read: F) -> crate::Result<Vec<u8>>
+where
+    F: FnMut(&mut [u8]) -> crate::Result<usize>,
+{
+    if header_len < 2 || length_offset > header_len - 2 {
+        return Err(Error::InvalidParam);
+    }
+
+    let mut header = vec![0u8; header_len];
+    let len = read(&mut header)?;
+    let total = match fields::le_u16_at(&header[..len], length_offset) {
+        Some(total) => usize::from(total),
+        None => {
+            return Err(Error::MalformedDescriptor {
+                offset: len,
+                reason: reason::NO_LENGTH,
+            })
+        }
+    };
+
+    let mut data = vec![0u8; total];
+    let len = read(&mut data)?;
+    data.truncate(len);
+
+    Ok(data)
+}
+
+/// Writes `bufs` in order with `write` until all are written or `timeout` elapses. See
+/// [`DeviceHandle::write_bulk_vectored`].
+fn write_slices<F>(bufs: &[IoSlice<'_>], timeout: Duration, mut write: F) -> crate::Result<usize>
+where
+    F: FnMut(&[u8], Duration) -> crate::Result<usize>,
+{
+    let deadline = deadline_after(timeout);
+    let mut total = 0;
+
+    for buf in bufs {
+        let remaining = match time_left(deadline) {
+            Some(remaining) => remaining,
+            None if total > 0 => break,
+            None => return Err(Error::Timeout),
+        };
+
+        match write(buf, remaining) {
+            Ok(n) => {
+                total += n;
+                if n < buf.len() {
+                    break;
+                }
+            }
+            Err(_) if total > 0 => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(total)
+}
+
+/// Writes `data` in blocks of `block_size` bytes with `write`, numbering them from
+/// `start_value`. See [`DeviceHandle::control_out_blocks`].
+fn write_blocks<F>(
+    data: &[u8],
+    block_size: usize,
+    start_value: u16,
+    mut write: F,
+) -> crate::Result<usize>
+where
+    F: FnMut(u16, &[u8]) -> crate::Result<usize>,
+{
+    if block_size == 0 || block_size > usize::from(u16::MAX) {
+        return Err(Error::InvalidParam);
+    }
+
+    let mut value = start_value;
+    let mut written = 0;
+
+    for block in data.chunks(block_size) {
+        match write(value, block) {
+            Ok(n) => {
+                written += n;
+                if n < block.len() {
+                    break;
+                }
+            }
+            Err(Error::Timeout | Error::Interrupted) if written > 0 => break,
+            Err(e) => return Err(e),
+        }
+        value = value.wrapping_add(1);
+    }
+
+    Ok(written)
+}
+
+/// Collects up to `count` reports of `report_size` bytes from `read`. See
+/// [`DeviceHandle::read_interrupt_multi`].
+fn read_reports<F>(report_size: usize, count: usize, mut read: F) -> crate::Result<Vec<Vec<u8>>>
+where
+    F: FnMut(&mut [u8]) -> crate::Result<usize>,
+{
+    // `count` is only an upper bound, so nothing is allocated for it up front.
+    let mut reports = Vec::new();
+
+    while reports.len() < count {
+        let mut report = vec![0; report_size];
+        match read(&mut report) {
+            Ok(n) => {
+                report.truncate(n);
+                reports.push(report);
+                if n < report_size {
+                    break;
+                }
+            }
+            Err(Error::Timeout) => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(reports)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ClaimedInterfaces;
-    use std::u8;
+    use super::{
+        bulk_buffer_size, check_string_descriptor, msos2_descriptor_set, poll_until,
+        preferred_language, read_at_least, read_reports, read_sized, test_mode_setup, write_blocks,
+        write_slices, ClaimedInterfaces,
+    };
+    use crate::{error::reason, test_helpers::reads, Error, Language, Speed};
+    #[cfg(not(feature = "no-global-context"))]
+    use crate::{Context, GlobalContext, UsbContext};
+    use std::{
+        io::IoSlice,
+        time::{Duration, Instant},
+        u8,
+    };
+
+    #[test]
+    fn msos2_descriptor_set_in_bos() {
+        let header = [0x05, 0x0F, 0x2C, 0x00, 0x02];
+        let usb2_extension = [0x07, 0x10, 0x02, 0x06, 0x00, 0x00, 0x00];
+        let platform = [
+            0x1C, 0x10, 0x05, 0x00, 0xDF, 0x60, 0xDD, 0xD8, 0x89, 0x45, 0xC7, 0x4C, 0x9C, 0xD2,
+            0x65, 0x9D, 0x9E, 0x64, 0x8A, 0x9F, 0x00, 0x00, 0x03, 0x06, 0xB2, 0x00, 0x21, 0x00,
+        ];
+        let bos = [&header[..], &usb2_extension[..], &platform[..]].concat();
+
+        assert_eq!(msos2_descriptor_set(&bos), Some((0x21, 0x00B2)));
+    }
+
+    #[test]
+    fn msos2_descriptor_set_missing() {
+        let bos = [
+            0x05, 0x0F, 0x0C, 0x00, 0x01, 0x07, 0x10, 0x02, 0x06, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(msos2_descriptor_set(&bos), None);
+        assert_eq!(msos2_descriptor_set(&[]), None);
+        assert_eq!(
+            msos2_descriptor_set(&[0x05, 0x0F, 0x0C, 0x00, 0x01, 0x00]),
+            None
+        );
+    }
 
     #[test]
     fn claimed_interfaces_empty() {
@@ -895,6 +2882,260 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    #[cfg(not(feature = "no-global-context"))]
+    fn global_context_outlives_its_users() {
+        context_or_skip!(Context::new());
+
+        let raw = GlobalContext::default().as_raw();
+        for _ in 0..2 {
+            let devices = GlobalContext::default().devices().unwrap();
+            // closing handles must not tear the global context down
+            for device in devices.iter() {
+                drop(device.open());
+            }
+            drop(devices);
+        }
+
+        assert_eq!(GlobalContext::default().as_raw(), raw);
+        assert!(GlobalContext::default().devices().is_ok());
+    }
+
+    #[test]
+    fn read_reports_up_to_count() {
+        let read = reads(vec![Ok(vec![1, 2]), Ok(vec![3, 4]), Ok(vec![5, 6])]);
+        assert_eq!(read_reports(2, 2, read), Ok(vec![vec![1, 2], vec![3, 4]]));
+    }
+
+    #[test]
+    fn read_reports_stops_on_timeout_or_short_read() {
+        let read = reads(vec![Ok(vec![1, 2]), Err(Error::Timeout), Ok(vec![3, 4])]);
+        assert_eq!(read_reports(2, 3, read), Ok(vec![vec![1, 2]]));
+
+        let read = reads(vec![Ok(vec![1, 2]), Ok(vec![3]), Ok(vec![4, 5])]);
+        assert_eq!(read_reports(2, 3, read), Ok(vec![vec![1, 2], vec![3]]));
+
+        assert_eq!(read_reports(2, 3, reads(vec![])), Ok(vec![]));
+    }
+
+    #[test]
+    fn read_reports_errors() {
+        let read = reads(vec![Err(Error::Pipe)]);
+        assert_eq!(read_reports(2, 3, read), Err(Error::Pipe));
+
+        let read = reads(vec![Ok(vec![1, 2]), Err(Error::Pipe)]);
+        assert_eq!(read_reports(2, 3, read), Err(Error::Pipe));
+
+        let read = reads(vec![Ok(vec![1, 2]), Ok(vec![3])]);
+        assert_eq!(
+            read_reports(2, usize::MAX, read),
+            Ok(vec![vec![1, 2], vec![3]])
+        );
+    }
+
+    #[test]
+    fn bulk_buffer_size_is_a_multiple_of_the_packet_size() {
+        assert_eq!(bulk_buffer_size(Speed::Full, Some(64)), 4096);
+        assert_eq!(bulk_buffer_size(Speed::High, Some(512)), 65536);
+        assert_eq!(bulk_buffer_size(Speed::Super, Some(1000)), 262000);
+        assert_eq!(bulk_buffer_size(Speed::High, Some(100_000)), 100_000);
+        assert_eq!(bulk_buffer_size(Speed::Unknown, None), 16384);
+    }
+
+    #[test]
+    fn write_blocks_numbers_blocks_in_value() {
+        let mut blocks = Vec::new();
+        let n = write_blocks(&[1, 2, 3, 4, 5], 2, u16::MAX, |value, block| {
+            blocks.push((value, block.to_vec()));
+            Ok(block.len())
+        });
+
+        assert_eq!(n, Ok(5));
+        assert_eq!(
+            blocks,
+            vec![(u16::MAX, vec![1, 2]), (0, vec![3, 4]), (1, vec![5])]
+        );
+    }
+
+    #[test]
+    fn write_blocks_stops_on_errors() {
+        assert_eq!(
+            write_blocks(&[1], 0, 0, |_, b| Ok(b.len())),
+            Err(Error::InvalidParam)
+        );
+
+        let mut results = vec![Err(Error::Timeout), Ok(2)];
+        assert_eq!(
+            write_blocks(&[1, 2, 3, 4], 2, 0, |_, _| results.pop().unwrap()),
+            Ok(2)
+        );
+
+        let mut results = vec![Err(Error::Pipe), Ok(2)];
+        assert_eq!(
+            write_blocks(&[1, 2, 3, 4], 2, 0, |_, _| results.pop().unwrap()),
+            Err(Error::Pipe)
+        );
+    }
+
+    #[test]
+    fn preferred_language_favors_english() {
+        let lang_ids = |ids: &[u16]| {
+            ids.iter()
+                .map(|&id| Language::from_lang_id(id))
+                .collect::<Vec<_>>()
+        };
+        let pick = |ids: &[u16]| preferred_language(&lang_ids(ids)).map(|l| l.lang_id());
+
+        assert_eq!(pick(&[0x0411, 0x0809, 0x0409]), Some(0x0409));
+        assert_eq!(pick(&[0x0411, 0x0809]), Some(0x0809));
+        assert_eq!(pick(&[0x0411, 0x0407]), Some(0x0411));
+        assert_eq!(pick(&[]), None);
+    }
+
+    #[test]
+    fn test_mode_setup_addresses_device_or_hub_port() {
+        let device = test_mode_setup(4, 0);
+        assert_eq!(device.request_type, 0x00);
+        assert_eq!(device.request, 0x03);
+        assert_eq!((device.value, device.index), (0x02, 0x0400));
+
+        let port = test_mode_setup(1, 3);
+        assert_eq!(port.request_type, 0x23);
+        assert_eq!(port.request, 0x03);
+        assert_eq!((port.value, port.index), (21, 0x0103));
+    }
+
+    #[test]
+    fn write_slices_in_order() {
+        let mut written = Vec::new();
+        let bufs = [IoSlice::new(&[1, 2]), IoSlice::new(&[]), IoSlice::new(&[3])];
+        let n = write_slices(&bufs, Duration::from_secs(1), |buf, _| {
+            written.push(buf.to_vec());
+            Ok(buf.len())
+        });
+
+        assert_eq!(n, Ok(3));
+        assert_eq!(written, vec![vec![1, 2], vec![], vec![3]]);
+    }
+
+    #[test]
+    fn write_slices_stops_after_partial_write() {
+        let bufs = [
+            IoSlice::new(&[1, 2]),
+            IoSlice::new(&[3, 4]),
+            IoSlice::new(&[5]),
+        ];
+        let mut results = vec![Err(Error::Pipe), Ok(1), Ok(2)];
+        let n = write_slices(&bufs, Duration::from_secs(1), |_, _| results.pop().unwrap());
+        assert_eq!(n, Ok(3));
+
+        let n = write_slices(&bufs, Duration::from_secs(1), |_, _| Err(Error::Pipe));
+        assert_eq!(n, Err(Error::Pipe));
+    }
+
+    #[test]
+    fn poll_until_retries_timeouts() {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut results = vec![Ok(4), Err(Error::Timeout), Err(Error::Timeout)];
+        let mut timeouts = Vec::new();
+        let n = poll_until(deadline, Duration::from_millis(5), |timeout| {
+            timeouts.push(timeout);
+            results.pop().unwrap()
+        });
+
+        assert_eq!(n, Ok(4));
+        assert_eq!(timeouts, vec![Duration::from_millis(5); 3]);
+
+        let n = poll_until(deadline, Duration::from_millis(5), |_| Err(Error::Pipe));
+        assert_eq!(n, Err(Error::Pipe));
+    }
+
+    #[test]
+    fn poll_until_gives_up_at_the_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let n = poll_until(deadline, Duration::from_secs(1), |timeout| {
+            assert!(timeout <= Duration::from_millis(20));
+            std::thread::sleep(timeout);
+            Err(Error::Timeout)
+        });
+
+        assert_eq!(n, Err(Error::Timeout));
+        assert!(Instant::now() + Duration::from_millis(1) >= deadline);
+    }
+
+    #[test]
+    fn read_at_least_continues_after_short_packets() {
+        let mut read = reads(vec![
+            Ok(vec![1, 2]),
+            Ok(vec![3]),
+            Ok(vec![4, 5]),
+            Ok(vec![6]),
+        ]);
+        let mut buf = [0; 8];
+        let n = read_at_least(&mut buf, 4, Duration::from_secs(1), |buf, _| read(buf));
+        assert_eq!(n, Ok(5));
+        assert_eq!(buf[..5], [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn read_at_least_returns_partial_data_on_error() {
+        let mut read = reads(vec![Ok(vec![1, 2]), Err(Error::Timeout)]);
+        let mut buf = [0; 8];
+        let n = read_at_least(&mut buf, 4, Duration::from_secs(1), |buf, _| read(buf));
+        assert_eq!(n, Ok(2));
+
+        let mut read = reads(vec![Err(Error::Pipe)]);
+        let n = read_at_least(&mut buf, 4, Duration::from_secs(1), |buf, _| read(buf));
+        assert_eq!(n, Err(Error::Pipe));
+
+        let n = read_at_least(&mut buf, 9, Duration::from_secs(1), |_, _| Ok(0));
+        assert_eq!(n, Err(Error::InvalidParam));
+    }
+
+    #[test]
+    fn read_sized_reads_header_then_body() {
+        let response = [
+            0x09, 0x02, 0x0C, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32, 0x01, 0x02, 0x03,
+        ];
+        let mut lengths = Vec::new();
+
+        let data = read_sized(9, 2, |buf| {
+            lengths.push(buf.len());
+            let n = buf.len().min(response.len());
+            buf[..n].copy_from_slice(&response[..n]);
+            Ok(n)
+        });
+
+        assert_eq!(data, Ok(response.to_vec()));
+        assert_eq!(lengths, [9, 12]);
+    }
+
+    #[test]
+    fn check_string_descriptor_lengths() {
+        let malformed = Err(Error::MalformedDescriptor {
+            offset: 0,
+            reason: reason::STRING_LENGTH,
+        });
+
+        assert_eq!(check_string_descriptor(&[2, 0x03]), Ok(()));
+        assert_eq!(check_string_descriptor(&[4, 0x03, 0x09, 0x04]), Ok(()));
+        assert_eq!(check_string_descriptor(&[]), malformed);
+        assert_eq!(check_string_descriptor(&[4, 0x03]), malformed);
+        assert_eq!(check_string_descriptor(&[3, 0x03, 0x41]), malformed);
+    }
+
+    #[test]
+    fn read_sized_rejects_short_headers() {
+        assert_eq!(read_sized(2, 1, |_| Ok(2)), Err(Error::InvalidParam));
+        assert_eq!(
+            read_sized(4, 2, |_| Ok(3)),
+            Err(Error::MalformedDescriptor {
+                offset: 3,
+                reason: reason::NO_LENGTH,
+            })
+        );
+    }
+
     #[test]
     fn claimed_interfaces_many_elements() {
         let mut interfaces = ClaimedInterfaces::new();
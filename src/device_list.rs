@@ -1,11 +1,15 @@
 use libc::c_int;
 
-use std::{mem, slice};
+use std::{fmt, mem, slice, time::Instant};
 
+#[cfg(not(feature = "no-global-context"))]
+use crate::context::GlobalContext;
 use crate::{
-    context::{GlobalContext, UsbContext},
+    context::UsbContext,
     device::{self, Device},
+    device_descriptor::DeviceDescriptor,
     error,
+    fields::Speed,
 };
 use libusb1_sys::*;
 
@@ -14,6 +18,7 @@ pub struct DeviceList<T: UsbContext> {
     context: T,
     list: *const *mut libusb_device,
     len: usize,
+    enumerated_at: Instant,
 }
 
 impl<T: UsbContext> Drop for DeviceList<T> {
@@ -25,6 +30,7 @@ impl<T: UsbContext> Drop for DeviceList<T> {
     }
 }
 
+#[cfg(not(feature = "no-global-context"))]
 impl DeviceList<GlobalContext> {
     pub fn new() -> crate::Result<DeviceList<GlobalContext>> {
         let mut list = mem::MaybeUninit::<*const *mut libusb_device>::uninit();
@@ -40,6 +46,7 @@ impl DeviceList<GlobalContext> {
                     context: Default::default(),
                     list: list.assume_init(),
                     len: n as usize,
+                    enumerated_at: Instant::now(),
                 }
             })
         }
@@ -47,6 +54,9 @@ impl DeviceList<GlobalContext> {
 }
 
 impl<T: UsbContext> DeviceList<T> {
+    /// Returns a list of the current USB devices of `context`.
+    ///
+    /// This is the same as [`UsbContext::devices`].
     pub fn new_with_context(context: T) -> crate::Result<DeviceList<T>> {
         let mut list = mem::MaybeUninit::<*const *mut libusb_device>::uninit();
 
@@ -60,11 +70,21 @@ impl<T: UsbContext> DeviceList<T> {
                     context,
                     list: list.assume_init(),
                     len: len as usize,
+                    enumerated_at: Instant::now(),
                 }
             })
         }
     }
 
+    /// Returns when the list was built, i.e. when `libusb` returned it.
+    ///
+    /// `Instant` is monotonic, so of two lists the one with the later instant reflects the
+    /// more recent state of the bus. This helps order snapshots taken by polling code and log
+    /// how stale a list is.
+    pub fn enumerated_at(&self) -> Instant {
+        self.enumerated_at
+    }
+
     /// Returns the number of devices in the list.
     pub fn len(&self) -> usize {
         self.len
@@ -85,6 +105,50 @@ impl<T: UsbContext> DeviceList<T> {
             index: 0,
         }
     }
+
+    /// Returns an iterator over the devices in the list together with their device
+    /// descriptors.
+    ///
+    /// Devices whose descriptor can't be read are skipped.
+    pub fn iter_with_descriptors(
+        &self,
+    ) -> impl Iterator<Item = (Device<T>, DeviceDescriptor)> + '_ {
+        self.iter().filter_map(|device| {
+            let descriptor = device.device_descriptor().ok()?;
+            Some((device, descriptor))
+        })
+    }
+
+    /// Returns an iterator over the devices in the list together with their connection speed.
+    ///
+    /// Reading the speed involves no I/O, see [`Device::speed`].
+    pub fn iter_with_speed(&self) -> impl Iterator<Item = (Device<T>, Speed)> + '_ {
+        self.iter().map(|device| {
+            let speed = device.speed();
+            (device, speed)
+        })
+    }
+}
+
+impl<T: UsbContext> IntoIterator for DeviceList<T> {
+    type Item = Device<T>;
+    type IntoIter = std::vec::IntoIter<Device<T>>;
+
+    /// Consumes the list and returns an iterator over its devices.
+    ///
+    /// Each `Device` holds its own reference, so the devices stay valid after the list is freed.
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter().collect::<Vec<_>>().into_iter()
+    }
+}
+
+impl<'a, T: UsbContext> IntoIterator for &'a DeviceList<T> {
+    type Item = Device<T>;
+    type IntoIter = Devices<'a, T>;
+
+    fn into_iter(self) -> Devices<'a, T> {
+        self.iter()
+    }
 }
 
 /// Iterator over detected USB devices.
@@ -118,3 +182,69 @@ impl<'a, T: UsbContext> Iterator for Devices<'a, T> {
         (remaining, Some(remaining))
     }
 }
+
+/// Repeatedly enumerates the devices of a context, reusing allocations between rounds.
+///
+/// This suits applications that poll for devices on a timer, for example on platforms without
+/// hotplug support. Each call to [`poll`](#method.poll) reads the current device list. Devices
+/// that were already present keep their `Device` value, including its cached device
+/// descriptor, and the buffers holding the devices are reused, so a round without changes
+/// allocates nothing beyond libusb's own list.
+pub struct DevicePoll<T: UsbContext> {
+    context: T,
+    devices: Vec<Device<T>>,
+    spare: Vec<Device<T>>,
+}
+
+impl<T: UsbContext> DevicePoll<T> {
+    /// Creates a poller for the devices of `context`. No devices are read until the first call
+    /// to [`poll`](#method.poll).
+    pub fn new(context: T) -> DevicePoll<T> {
+        DevicePoll {
+            context,
+            devices: Vec::new(),
+            spare: Vec::new(),
+        }
+    }
+
+    /// Returns the context whose devices are polled.
+    pub fn context(&self) -> &T {
+        &self.context
+    }
+
+    /// Returns the devices found by the last successful call to [`poll`](#method.poll).
+    pub fn devices(&self) -> &[Device<T>] {
+        &self.devices
+    }
+
+    /// Re-enumerates the devices and returns the current list.
+    ///
+    /// Devices that disappeared since the last call are released. On error the previous list is
+    /// kept.
+    pub fn poll(&mut self) -> crate::Result<&[Device<T>]> {
+        let list = self.context.devices()?;
+
+        self.spare.clear();
+        for device in list.iter() {
+            match self
+                .devices
+                .iter()
+                .position(|known| known.as_raw() == device.as_raw())
+            {
+                Some(index) => self.spare.push(self.devices.swap_remove(index)),
+                None => self.spare.push(device),
+            }
+        }
+
+        mem::swap(&mut self.devices, &mut self.spare);
+        self.spare.clear();
+
+        Ok(&self.devices)
+    }
+}
+
+impl<T: UsbContext> fmt::Debug for DevicePoll<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.devices.iter()).finish()
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    device_handle::DeviceHandle,
+    fields::{interface_index, request_type, ControlSetup, Direction, Recipient, RequestType},
+    Error, UsbContext,
+};
+
+/// `bRequest` of the DFU class request `DFU_DETACH`.
+const DFU_DETACH: u8 = 0x00;
+
+/// `bRequest` of the DFU class request `DFU_GETSTATUS`.
+const DFU_GETSTATUS: u8 = 0x03;
+
+/// The state of a DFU device, as reported by `DFU_GETSTATUS`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DfuState {
+    /// `appIDLE`: the device is running its normal application.
+    AppIdle,
+
+    /// `appDETACH`: the device received `DFU_DETACH` and waits for a USB reset.
+    AppDetach,
+
+    /// `dfuIDLE`: the device is in DFU mode, waiting for requests.
+    DfuIdle,
+
+    /// `dfuDNLOAD-SYNC`: the device received a block and waits for `DFU_GETSTATUS`.
+    DownloadSync,
+
+    /// `dfuDNBUSY`: the device is programming a block.
+    DownloadBusy,
+
+    /// `dfuDNLOAD-IDLE`: the device waits for the next block.
+    DownloadIdle,
+
+    /// `dfuMANIFEST-SYNC`: the device received the last block and waits for `DFU_GETSTATUS`.
+    ManifestSync,
+
+    /// `dfuMANIFEST`: the device is in the manifestation phase.
+    Manifest,
+
+    /// `dfuMANIFEST-WAIT-RESET`: the device waits for a USB reset after manifestation.
+    ManifestWaitReset,
+
+    /// `dfuUPLOAD-IDLE`: the device is in the middle of an upload.
+    UploadIdle,
+
+    /// `dfuERROR`: an error occurred; the status tells which.
+    Error,
+
+    /// A state not defined by the DFU 1.1 specification.
+    Unknown(u8),
+}
+
+impl DfuState {
+    fn from_u8(state: u8) -> DfuState {
+        match state {
+            0 => DfuState::AppIdle,
+            1 => DfuState::AppDetach,
+            2 => DfuState::DfuIdle,
+            3 => DfuState::DownloadSync,
+            4 => DfuState::DownloadBusy,
+            5 => DfuState::DownloadIdle,
+            6 => DfuState::ManifestSync,
+            7 => DfuState::Manifest,
+            8 => DfuState::ManifestWaitReset,
+            9 => DfuState::UploadIdle,
+            10 => DfuState::Error,
+            state => DfuState::Unknown(state),
+        }
+    }
+}
+
+/// The response of a DFU device to `DFU_GETSTATUS`.
+///
+/// Read with [`DeviceHandle::dfu_get_status`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DfuStatus {
+    status: u8,
+    poll_timeout: u32,
+    state: u8,
+    string_index: u8,
+}
+
+impl DfuStatus {
+    /// Returns the `bStatus` field, the result of the most recent request. 0 means `OK`; the
+    /// other codes, e.g. 0x03 `errWRITE`, are listed in the DFU specification.
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    /// Returns true if the most recent request succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.status == 0
+    }
+
+    /// Returns how long the host has to wait before sending the next `DFU_GETSTATUS`, from the
+    /// `bwPollTimeout` field.
+    pub fn poll_timeout(&self) -> Duration {
+        Duration::from_millis(u64::from(self.poll_timeout))
+    }
+
+    /// Returns the state the device enters right after this response.
+    pub fn state(&self) -> DfuState {
+        DfuState::from_u8(self.state)
+    }
+
+    /// Returns the index of a string descriptor describing the status, if any.
+    pub fn string_index(&self) -> Option<u8> {
+        match self.string_index {
+            0 => None,
+            n => Some(n),
+        }
+    }
+}
+
+/// Parses the 6-byte response to `DFU_GETSTATUS`.
+fn parse_status(response: &[u8; 6]) -> DfuStatus {
+    DfuStatus {
+        status: response[0],
+        poll_timeout: u32::from_le_bytes([response[1], response[2], response[3], 0]),
+        state: response[4],
+        string_index: response[5],
+    }
+}
+
+/// Requests of the DFU class (Device Firmware Upgrade 1.1), addressed to the DFU interface
+/// `iface`.
+impl<T: UsbContext> DeviceHandle<T> {
+    /// Reads the status of the DFU interface `iface` with `DFU_GETSTATUS`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Io` if the device answers with fewer than 6 bytes. Otherwise the errors are the
+    /// same as for [`read_control`](#method.read_control).
+    pub fn dfu_get_status(&self, iface: u8, timeout: Duration) -> crate::Result<DfuStatus> {
+        let mut response = [0u8; 6];
+
+        let len = self.read_control(
+            request_type(Direction::In, RequestType::Class, Recipient::Interface),
+            DFU_GETSTATUS,
+            0,
+            interface_index(iface),
+            &mut response,
+            timeout,
+        )?;
+
+        match len {
+            6 => Ok(parse_status(&response)),
+            _ => Err(Error::Io),
+        }
+    }
+
+    /// Asks the device to switch to DFU mode with `DFU_DETACH` on the DFU interface `iface`.
+    ///
+    /// `detach_timeout_ms` is the time in milliseconds the device waits for the USB reset that
+    /// completes the switch; it must not exceed the `wDetachTimeOut` of the DFU functional
+    /// descriptor. Devices that don't set `bitWillDetach` there need to be reset by the host,
+    /// e.g. with [`reset`](#method.reset), once this returns.
+    pub fn dfu_detach(
+        &self,
+        iface: u8,
+        detach_timeout_ms: u16,
+        timeout: Duration,
+    ) -> crate::Result<()> {
+        self.control_no_data(
+            ControlSetup {
+                request_type: request_type(
+                    Direction::Out,
+                    RequestType::Class,
+                    Recipient::Interface,
+                ),
+                request: DFU_DETACH,
+                value: detach_timeout_ms,
+                index: interface_index(iface),
+                length: 0,
+            },
+            timeout,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_status, DfuState};
+    use std::time::Duration;
+
+    #[test]
+    fn it_parses_status_response() {
+        let status = parse_status(&[0x00, 0x10, 0x27, 0x00, 0x05, 0x00]);
+
+        assert!(status.is_ok());
+        assert_eq!(status.poll_timeout(), Duration::from_millis(10_000));
+        assert_eq!(status.state(), DfuState::DownloadIdle);
+        assert_eq!(status.string_index(), None);
+    }
+
+    #[test]
+    fn it_parses_error_status_response() {
+        let status = parse_status(&[0x03, 0xFF, 0xFF, 0xFF, 0x0A, 0x04]);
+
+        assert!(!status.is_ok());
+        assert_eq!(status.status(), 0x03);
+        assert_eq!(status.poll_timeout(), Duration::from_millis(0xFF_FFFF));
+        assert_eq!(status.state(), DfuState::Error);
+        assert_eq!(status.string_index(), Some(4));
+    }
+
+    #[test]
+    fn it_keeps_unknown_states() {
+        let status = parse_status(&[0x00, 0x00, 0x00, 0x00, 0x42, 0x00]);
+        assert_eq!(status.state(), DfuState::Unknown(0x42));
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    slice,
+};
+
+use libusb1_sys::{libusb_dev_mem_free, libusb_device_handle};
+
+use crate::{device_handle::DeviceHandle, UsbContext};
+
+/// A buffer allocated by the kernel for zero-copy transfers on a device.
+///
+/// Created by [`DeviceHandle::alloc_dma_buffer`]. The memory is released with
+/// `libusb_dev_mem_free` when the buffer is dropped, so it can't outlive the handle it was
+/// allocated for.
+pub struct DmaBuffer<'a, T: UsbContext> {
+    handle: *mut libusb_device_handle,
+    buffer: NonNull<u8>,
+    len: usize,
+    _handle: PhantomData<&'a DeviceHandle<T>>,
+}
+
+impl<'a, T: UsbContext> DmaBuffer<'a, T> {
+    /// Returns the length of the buffer in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the buffer has a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a, T: UsbContext> Drop for DmaBuffer<'a, T> {
+    /// Releases the buffer back to the kernel.
+    fn drop(&mut self) {
+        unsafe {
+            libusb_dev_mem_free(self.handle, self.buffer.as_ptr(), self.len);
+        }
+    }
+}
+
+unsafe impl<'a, T: UsbContext> Send for DmaBuffer<'a, T> {}
+unsafe impl<'a, T: UsbContext> Sync for DmaBuffer<'a, T> {}
+
+impl<'a, T: UsbContext> Deref for DmaBuffer<'a, T> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.buffer.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T: UsbContext> DerefMut for DmaBuffer<'a, T> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T: UsbContext> Debug for DmaBuffer<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DmaBuffer")
+            .field("buffer", &self.buffer)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[doc(hidden)]
+pub(crate) unsafe fn from_libusb<T: UsbContext>(
+    handle: &DeviceHandle<T>,
+    buffer: NonNull<u8>,
+    len: usize,
+) -> DmaBuffer<'_, T> {
+    DmaBuffer {
+        handle: handle.as_raw(),
+        buffer,
+        len,
+        _handle: PhantomData,
+    }
+}
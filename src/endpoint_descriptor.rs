@@ -1,10 +1,24 @@
-use std::{fmt, slice};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    slice,
+    time::Duration,
+};
 
 use libusb1_sys::{constants::*, libusb_endpoint_descriptor};
 
-use crate::fields::{Direction, SyncType, TransferType, UsageType};
+use crate::fields::{Direction, Speed, SyncType, TransferType, UsageType};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Describes an endpoint.
+///
+/// Descriptors compare equal and hash alike when they have the same endpoint address, so that
+/// the endpoints of several interfaces or alternate settings can be collected into a set without
+/// duplicates. The other fields, such as the maximum packet size, may differ between alternate
+/// settings and are left out; compare [`to_owned`](#method.to_owned) values to take them into
+/// account.
 pub struct EndpointDescriptor<'a> {
     descriptor: &'a libusb_endpoint_descriptor,
 }
@@ -48,6 +62,36 @@ impl<'a> EndpointDescriptor<'a> {
         }
     }
 
+    /// Indicates if the endpoint transfers data from the device to the host.
+    pub fn is_input(&self) -> bool {
+        self.direction() == Direction::In
+    }
+
+    /// Indicates if the endpoint transfers data from the host to the device.
+    pub fn is_output(&self) -> bool {
+        self.direction() == Direction::Out
+    }
+
+    /// Indicates if the endpoint is a bulk endpoint.
+    pub fn is_bulk(&self) -> bool {
+        self.transfer_type() == TransferType::Bulk
+    }
+
+    /// Indicates if the endpoint is an interrupt endpoint.
+    pub fn is_interrupt(&self) -> bool {
+        self.transfer_type() == TransferType::Interrupt
+    }
+
+    /// Indicates if the endpoint is an isochronous endpoint.
+    pub fn is_isochronous(&self) -> bool {
+        self.transfer_type() == TransferType::Isochronous
+    }
+
+    /// Indicates if the endpoint is a control endpoint.
+    pub fn is_control(&self) -> bool {
+        self.transfer_type() == TransferType::Control
+    }
+
     /// Returns the endpoint's synchronisation mode.
     ///
     /// The return value of this method is only valid for isochronous endpoints.
@@ -72,16 +116,124 @@ impl<'a> EndpointDescriptor<'a> {
         }
     }
 
-    /// Returns the endpoint's maximum packet size.
+    /// Returns the endpoint's raw `wMaxPacketSize` field.
+    ///
+    /// For high speed isochronous and interrupt endpoints, bits 11..12 of the field hold the
+    /// number of additional transactions per microframe, so the value is not a packet size. Use
+    /// [`max_packet_size_bytes`](#method.max_packet_size_bytes) for the size of one packet and
+    /// [`transactions_per_microframe`](#method.transactions_per_microframe) for the multiplier.
     pub fn max_packet_size(&self) -> u16 {
         self.descriptor.wMaxPacketSize
     }
 
+    /// Returns the maximum size of a single packet in bytes, from bits 0..10 of
+    /// `wMaxPacketSize`.
+    pub fn max_packet_size_bytes(&self) -> u16 {
+        self.descriptor.wMaxPacketSize & 0x07FF
+    }
+
+    /// Returns the number of transactions per microframe, from 1 to 3, encoded in bits 11..12
+    /// of `wMaxPacketSize`.
+    ///
+    /// Only high speed isochronous and interrupt endpoints use more than one transaction per
+    /// microframe, so the bits are 0 and this returns 1 for other endpoints.
+    pub fn transactions_per_microframe(&self) -> u8 {
+        (((self.descriptor.wMaxPacketSize >> 11) & 0x03) as u8 + 1).min(3)
+    }
+
     /// Returns the endpoint's polling interval.
     pub fn interval(&self) -> u8 {
         self.descriptor.bInterval
     }
 
+    /// Returns the endpoint's polling period for a device operating at `speed`.
+    ///
+    /// The meaning of `bInterval` depends on the endpoint's transfer type and the device speed.
+    /// For interrupt endpoints on low and full speed devices it is a number of 1 ms frames. For
+    /// isochronous endpoints, and for all periodic endpoints at high speed and above, it is an
+    /// exponent: the period is 2^(`bInterval`-1) frames (full speed) or 125 µs microframes (high
+    /// speed and above).
+    ///
+    /// Returns `None` for bulk and control endpoints, for an out of range `bInterval`, or if the
+    /// speed is unknown.
+    pub fn polling_interval(&self, speed: Speed) -> Option<Duration> {
+        let interval = u32::from(self.descriptor.bInterval);
+        let transfer_type = self.transfer_type();
+
+        match transfer_type {
+            TransferType::Interrupt | TransferType::Isochronous => (),
+            TransferType::Bulk | TransferType::Control => return None,
+        }
+
+        match speed {
+            Speed::Low | Speed::Full => {
+                if transfer_type == TransferType::Interrupt {
+                    match interval {
+                        0 => None,
+                        n => Some(Duration::from_millis(u64::from(n))),
+                    }
+                } else {
+                    match interval {
+                        1..=16 => Some(Duration::from_millis(1 << (interval - 1))),
+                        _ => None,
+                    }
+                }
+            }
+            Speed::High | Speed::Super | Speed::SuperPlus => match interval {
+                1..=16 => Some(Duration::from_micros(125 << (interval - 1))),
+                _ => None,
+            },
+            Speed::Unknown => None,
+        }
+    }
+
+    /// Returns the number of bytes the endpoint can move in one service interval for a device
+    /// operating at `speed`.
+    ///
+    /// At low and full speed this is the maximum packet size. High speed periodic endpoints can
+    /// carry up to three packets per microframe, encoded in bits 11..12 of `wMaxPacketSize`. At
+    /// SuperSpeed and above, the SuperSpeed endpoint companion descriptor found in the endpoint's
+    /// extra bytes adds a burst of up to 16 packets and, for isochronous endpoints, a multiplier of
+    /// up to 3 bursts.
+    pub fn max_transfer_per_interval(&self, speed: Speed) -> u32 {
+        let packet_size = u32::from(self.max_packet_size_bytes());
+        let periodic = matches!(
+            self.transfer_type(),
+            TransferType::Interrupt | TransferType::Isochronous
+        );
+
+        match speed {
+            Speed::High if periodic => packet_size * u32::from(self.transactions_per_microframe()),
+            Speed::Super | Speed::SuperPlus => match self.ss_companion() {
+                Some(companion) => {
+                    let burst = u32::from(companion[2]) + 1;
+                    let mult = match self.transfer_type() {
+                        TransferType::Isochronous => u32::from(companion[3] & 0x03) + 1,
+                        _ => 1,
+                    };
+                    packet_size * burst * mult
+                }
+                None => packet_size,
+            },
+            _ => packet_size,
+        }
+    }
+
+    /// Returns the SuperSpeed endpoint companion descriptor from the extra bytes, if present.
+    fn ss_companion(&self) -> Option<&[u8]> {
+        let mut extra = self.extra()?;
+
+        while extra.len() >= 2 && extra[0] >= 2 && extra.len() >= extra[0] as usize {
+            let (descriptor, rest) = extra.split_at(extra[0] as usize);
+            if descriptor[1] == LIBUSB_DT_SS_ENDPOINT_COMPANION && descriptor.len() >= 6 {
+                return Some(descriptor);
+            }
+            extra = rest;
+        }
+
+        None
+    }
+
     /// Returns the unknown 'extra' bytes that libusb does not understand.
     pub fn extra(&'a self) -> Option<&'a [u8]> {
         unsafe {
@@ -95,14 +247,110 @@ impl<'a> EndpointDescriptor<'a> {
         }
     }
 
+    /// Returns true if this is the 9-byte endpoint descriptor of a USB Audio 1.0 device, which
+    /// adds the `bRefresh` and `bSynchAddress` fields to the standard 7 bytes.
+    pub fn is_audio_endpoint(&self) -> bool {
+        self.descriptor.bLength >= LIBUSB_DT_ENDPOINT_AUDIO_SIZE
+    }
+
     /// For audio devices only: return the rate at which synchronization feedback is provided.
-    pub fn refresh(&self) -> u8 {
-        self.descriptor.bRefresh
+    ///
+    /// Returns `None` if the descriptor has no `bRefresh` field, see
+    /// [`is_audio_endpoint`](#method.is_audio_endpoint).
+    pub fn refresh(&self) -> Option<u8> {
+        if self.is_audio_endpoint() {
+            Some(self.descriptor.bRefresh)
+        } else {
+            None
+        }
     }
 
     /// For audio devices only: return the address if the synch endpoint.
-    pub fn synch_address(&self) -> u8 {
-        self.descriptor.bSynchAddress
+    ///
+    /// Returns `None` if the descriptor has no `bSynchAddress` field, see
+    /// [`is_audio_endpoint`](#method.is_audio_endpoint).
+    pub fn synch_address(&self) -> Option<u8> {
+        if self.is_audio_endpoint() {
+            Some(self.descriptor.bSynchAddress)
+        } else {
+            None
+        }
+    }
+
+    /// Copies the fields a driver needs to use the endpoint into an [`OwnedEndpointInfo`],
+    /// which doesn't borrow the configuration descriptor.
+    pub fn to_owned(&self) -> OwnedEndpointInfo {
+        OwnedEndpointInfo {
+            address: self.address(),
+            transfer_type: self.transfer_type(),
+            max_packet_size: self.max_packet_size(),
+            interval: self.interval(),
+        }
+    }
+}
+
+/// The fields of an endpoint descriptor needed to perform transfers on it.
+///
+/// Unlike an [`EndpointDescriptor`], which borrows the
+/// [`ConfigDescriptor`](crate::ConfigDescriptor) it belongs to, this can be stored for as long as
+/// the endpoint is used, after the configuration descriptor is dropped. Created by
+/// [`EndpointDescriptor::to_owned`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedEndpointInfo {
+    address: u8,
+    transfer_type: TransferType,
+    max_packet_size: u16,
+    interval: u8,
+}
+
+impl OwnedEndpointInfo {
+    /// Returns the endpoint's address.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the endpoint number.
+    pub fn number(&self) -> u8 {
+        self.address & LIBUSB_ENDPOINT_ADDRESS_MASK
+    }
+
+    /// Returns the endpoint's direction.
+    pub fn direction(&self) -> Direction {
+        match self.address & LIBUSB_ENDPOINT_DIR_MASK {
+            LIBUSB_ENDPOINT_OUT => Direction::Out,
+            LIBUSB_ENDPOINT_IN | _ => Direction::In,
+        }
+    }
+
+    /// Returns the endpoint's transfer type.
+    pub fn transfer_type(&self) -> TransferType {
+        self.transfer_type
+    }
+
+    /// Returns the endpoint's raw `wMaxPacketSize` field, see
+    /// [`EndpointDescriptor::max_packet_size`].
+    pub fn max_packet_size(&self) -> u16 {
+        self.max_packet_size
+    }
+
+    /// Returns the endpoint's polling interval.
+    pub fn interval(&self) -> u8 {
+        self.interval
+    }
+}
+
+impl<'a> PartialEq for EndpointDescriptor<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl<'a> Eq for EndpointDescriptor<'a> {}
+
+impl<'a> Hash for EndpointDescriptor<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address().hash(state);
     }
 }
 
@@ -130,7 +378,26 @@ pub(crate) fn from_libusb(endpoint: &libusb_endpoint_descriptor) -> EndpointDesc
 
 #[cfg(test)]
 mod test {
-    use crate::fields::{Direction, SyncType, TransferType, UsageType};
+    use crate::fields::{Direction, Speed, SyncType, TransferType, UsageType};
+    use std::{collections::HashSet, time::Duration};
+
+    #[test]
+    fn it_compares_by_address() {
+        let bulk = endpoint_descriptor!(bEndpointAddress: 0x81, bmAttributes: 0x02);
+        let bulk_large = endpoint_descriptor!(
+            bEndpointAddress: 0x81, bmAttributes: 0x02, wMaxPacketSize: 512
+        );
+        let out = endpoint_descriptor!(bEndpointAddress: 0x01, bmAttributes: 0x02);
+
+        assert_eq!(super::from_libusb(&bulk), super::from_libusb(&bulk_large));
+        assert_ne!(super::from_libusb(&bulk), super::from_libusb(&out));
+
+        let endpoints = [&bulk, &bulk_large, &out]
+            .iter()
+            .map(|endpoint| super::from_libusb(endpoint))
+            .collect::<HashSet<_>>();
+        assert_eq!(2, endpoints.len());
+    }
 
     #[test]
     fn it_interprets_number_for_output_endpoints() {
@@ -268,6 +535,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_copies_fields_to_owned_info() {
+        let info = super::from_libusb(&endpoint_descriptor!(
+            bEndpointAddress: 0x83,
+            bmAttributes: 0x03,
+            wMaxPacketSize: 0x0840,
+            bInterval: 4
+        ))
+        .to_owned();
+
+        assert_eq!(info.address(), 0x83);
+        assert_eq!(info.number(), 3);
+        assert_eq!(info.direction(), Direction::In);
+        assert_eq!(info.transfer_type(), TransferType::Interrupt);
+        assert_eq!(info.max_packet_size(), 0x0840);
+        assert_eq!(info.interval(), 4);
+
+        let info = super::from_libusb(&endpoint_descriptor!(bEndpointAddress: 0x8A)).to_owned();
+        assert_eq!(info.number(), 10);
+        assert_eq!(info.direction(), Direction::In);
+    }
+
     #[test]
     fn it_has_interval() {
         assert_eq!(
@@ -283,4 +572,174 @@ mod test {
             super::from_libusb(&endpoint_descriptor!(bInterval: 255)).interval()
         );
     }
+
+    #[test]
+    fn it_has_polling_interval_for_full_speed_interrupt_endpoints() {
+        assert_eq!(
+            Some(Duration::from_millis(10)),
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x03, bInterval: 10))
+                .polling_interval(Speed::Full)
+        );
+        assert_eq!(
+            None,
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x03, bInterval: 0))
+                .polling_interval(Speed::Low)
+        );
+    }
+
+    #[test]
+    fn it_has_polling_interval_for_full_speed_isochronous_endpoints() {
+        assert_eq!(
+            Some(Duration::from_millis(8)),
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x01, bInterval: 4))
+                .polling_interval(Speed::Full)
+        );
+    }
+
+    #[test]
+    fn it_has_polling_interval_for_high_speed_endpoints() {
+        assert_eq!(
+            Some(Duration::from_micros(125)),
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x03, bInterval: 1))
+                .polling_interval(Speed::High)
+        );
+        assert_eq!(
+            Some(Duration::from_millis(1)),
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x01, bInterval: 4))
+                .polling_interval(Speed::Super)
+        );
+        assert_eq!(
+            None,
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x03, bInterval: 17))
+                .polling_interval(Speed::High)
+        );
+    }
+
+    #[test]
+    fn it_has_no_polling_interval_for_bulk_endpoints() {
+        assert_eq!(
+            None,
+            super::from_libusb(&endpoint_descriptor!(bmAttributes: 0x02, bInterval: 1))
+                .polling_interval(Speed::High)
+        );
+    }
+
+    #[test]
+    fn it_has_direction_predicates() {
+        let input = endpoint_descriptor!(bEndpointAddress: 0x81);
+        let output = endpoint_descriptor!(bEndpointAddress: 0x01);
+
+        assert!(super::from_libusb(&input).is_input());
+        assert!(!super::from_libusb(&input).is_output());
+        assert!(super::from_libusb(&output).is_output());
+        assert!(!super::from_libusb(&output).is_input());
+    }
+
+    #[test]
+    fn it_has_transfer_type_predicates() {
+        let control = endpoint_descriptor!(bmAttributes: 0x00);
+        let isochronous = endpoint_descriptor!(bmAttributes: 0x01);
+        let bulk = endpoint_descriptor!(bmAttributes: 0x02);
+        let interrupt = endpoint_descriptor!(bmAttributes: 0x03);
+
+        assert!(super::from_libusb(&control).is_control());
+        assert!(super::from_libusb(&isochronous).is_isochronous());
+        assert!(super::from_libusb(&bulk).is_bulk());
+        assert!(super::from_libusb(&interrupt).is_interrupt());
+        assert!(!super::from_libusb(&bulk).is_interrupt());
+        assert!(!super::from_libusb(&interrupt).is_bulk());
+    }
+
+    #[test]
+    fn it_has_max_transfer_per_interval_at_full_speed() {
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x01, wMaxPacketSize: 0x03FF);
+        assert_eq!(
+            1023,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::Full)
+        );
+    }
+
+    #[test]
+    fn it_has_max_transfer_per_interval_with_high_bandwidth_transactions() {
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x01, wMaxPacketSize: 0x1400);
+        assert_eq!(
+            3072,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::High)
+        );
+
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x02, wMaxPacketSize: 0x1200);
+        assert_eq!(
+            512,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::High)
+        );
+    }
+
+    #[test]
+    fn it_has_max_transfer_per_interval_with_ss_companion() {
+        let companion = [6u8, 0x30, 15, 0x02, 0x00, 0xC0];
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x01, wMaxPacketSize: 1024, extra: companion.as_ptr(), extra_length: 6);
+        assert_eq!(
+            1024 * 16 * 3,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::Super)
+        );
+
+        let companion = [6u8, 0x30, 3, 0x04, 0x00, 0x00];
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x02, wMaxPacketSize: 1024, extra: companion.as_ptr(), extra_length: 6);
+        assert_eq!(
+            1024 * 4,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::Super)
+        );
+    }
+
+    #[test]
+    fn it_has_max_transfer_per_interval_without_ss_companion() {
+        let endpoint = endpoint_descriptor!(bmAttributes: 0x02, wMaxPacketSize: 1024);
+        assert_eq!(
+            1024,
+            super::from_libusb(&endpoint).max_transfer_per_interval(Speed::Super)
+        );
+    }
+
+    #[test]
+    fn it_has_audio_fields_only_for_audio_endpoints() {
+        let descriptor = endpoint_descriptor!(bLength: 9, bRefresh: 3, bSynchAddress: 0x82);
+        let endpoint = super::from_libusb(&descriptor);
+        assert!(endpoint.is_audio_endpoint());
+        assert_eq!(Some(3), endpoint.refresh());
+        assert_eq!(Some(0x82), endpoint.synch_address());
+
+        let descriptor = endpoint_descriptor!(bLength: 7, bRefresh: 3, bSynchAddress: 0x82);
+        let endpoint = super::from_libusb(&descriptor);
+        assert!(!endpoint.is_audio_endpoint());
+        assert_eq!(None, endpoint.refresh());
+        assert_eq!(None, endpoint.synch_address());
+    }
+
+    #[test]
+    fn it_splits_max_packet_size_field() {
+        let descriptor = endpoint_descriptor!(wMaxPacketSize: 0x1400);
+        let endpoint = super::from_libusb(&descriptor);
+        assert_eq!(0x1400, endpoint.max_packet_size());
+        assert_eq!(1024, endpoint.max_packet_size_bytes());
+        assert_eq!(3, endpoint.transactions_per_microframe());
+
+        let descriptor = endpoint_descriptor!(wMaxPacketSize: 0x0200);
+        let endpoint = super::from_libusb(&descriptor);
+        assert_eq!(512, endpoint.max_packet_size_bytes());
+        assert_eq!(1, endpoint.transactions_per_microframe());
+
+        let descriptor = endpoint_descriptor!(wMaxPacketSize: 0x0BFF);
+        let endpoint = super::from_libusb(&descriptor);
+        assert_eq!(1023, endpoint.max_packet_size_bytes());
+        assert_eq!(2, endpoint.transactions_per_microframe());
+    }
+
+    #[test]
+    fn it_caps_reserved_transactions_per_microframe() {
+        let descriptor = endpoint_descriptor!(wMaxPacketSize: 0x1C00);
+        assert_eq!(
+            3,
+            super::from_libusb(&descriptor).transactions_per_microframe()
+        );
+    }
 }
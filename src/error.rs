@@ -3,14 +3,21 @@ use std::{fmt, result};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use libc::c_int;
 use libusb1_sys::constants::*;
 
 /// A result of a function that may return a `Error`.
 pub type Result<T> = result::Result<T, Error>;
 
 /// Errors returned by the `libusb` library.
+///
+/// The enum is `#[non_exhaustive]`, so that new kinds of errors such as
+/// [`MalformedDescriptor`](Error::MalformedDescriptor) can be added without breaking downstream
+/// code. Making it so was itself a breaking change: a `match` on an `Error` outside rusb needs a
+/// wildcard arm.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum Error {
     /// Input/output error.
     Io,
@@ -46,17 +53,92 @@ pub enum Error {
     NoMem,
 
     /// Operation not supported or unimplemented on this platform.
+    ///
+    /// On Windows this usually means that the device has no driver bound that `libusb` can use,
+    /// see [`Device::open`](crate::Device::open).
     NotSupported,
 
     /// The device returned a malformed descriptor.
     BadDescriptor,
 
+    /// A descriptor could not be parsed.
+    ///
+    /// Unlike `BadDescriptor`, this tells where in the descriptor parsing failed and why.
+    MalformedDescriptor {
+        /// The offset in bytes of the offending field from the start of the descriptor.
+        offset: usize,
+
+        /// What was wrong with the descriptor.
+        ///
+        /// With the `serde` feature, only the reasons reported by rusb itself can be
+        /// deserialized, as the field is a static string.
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_reason"))]
+        reason: Reason,
+    },
+
     /// Other error.
     Other,
 }
 
+/// A reason in [`Error::MalformedDescriptor`].
+///
+/// `serde` would take a `&'static str` field to be borrowed from the input, which only works for
+/// input that lives forever. Spelling it through an alias avoids that, as the reason is mapped to
+/// a static string by `deserialize_reason` instead.
+type Reason = &'static str;
+
+/// The reasons given by rusb in [`Error::MalformedDescriptor`].
+pub(crate) mod reason {
+    pub const NOT_CONFIG: &str = "bDescriptorType is not CONFIGURATION";
+    pub const NOT_BOS: &str = "bDescriptorType is not BOS";
+    pub const NO_LENGTH: &str = "response ends before its length field";
+    pub const STRING_LENGTH: &str = "bLength is not the even length of the string descriptor";
+
+    #[cfg(feature = "serde")]
+    pub const ALL: &[&str] = &[NOT_CONFIG, NOT_BOS, NO_LENGTH, STRING_LENGTH];
+}
+
+/// Maps a deserialized reason back to the static string rusb reported it with.
+#[cfg(feature = "serde")]
+fn deserialize_reason<'de, D>(deserializer: D) -> result::Result<&'static str, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    reason::ALL
+        .iter()
+        .find(|reason| **reason == text)
+        .copied()
+        .ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&text),
+                &"a reason reported by rusb",
+            )
+        })
+}
+
+impl Error {
+    /// Converts an error code returned by a raw `libusb` function into an `Error`.
+    ///
+    /// This is useful for code that mixes calls through [`ffi`](crate::ffi) with rusb. Codes
+    /// that don't correspond to a known `libusb` error map to `Error::Other`.
+    ///
+    /// ```
+    /// use rusb::{constants::LIBUSB_ERROR_TIMEOUT, Error};
+    ///
+    /// assert_eq!(Error::from_libusb(LIBUSB_ERROR_TIMEOUT), Error::Timeout);
+    /// ```
+    pub fn from_libusb(code: c_int) -> Error {
+        from_libusb(code)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        if let Error::MalformedDescriptor { offset, reason } = self {
+            return write!(fmt, "Malformed descriptor at offset {}: {}", offset, reason);
+        }
+
         fmt.write_str(match self {
             Error::Io => "Input/Output Error",
             Error::InvalidParam => "Invalid parameter",
@@ -70,7 +152,7 @@ impl fmt::Display for Error {
             Error::Interrupted => "System call interrupted (perhaps due to signal)",
             Error::NoMem => "Insufficient memory",
             Error::NotSupported => "Operation not supported or unimplemented on this platform",
-            Error::BadDescriptor => "Malformed descriptor",
+            Error::BadDescriptor | Error::MalformedDescriptor { .. } => "Malformed descriptor",
             Error::Other => "Other error",
         })
     }
@@ -78,6 +160,41 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+/// The error returned by [`DeviceHandle::control_sequence`](crate::DeviceHandle::control_sequence).
+///
+/// It identifies which transfer of the sequence failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequenceError {
+    /// The index of the failed transfer in the sequence.
+    pub index: usize,
+
+    /// The error that occurred.
+    pub error: Error,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "Transfer {} of sequence failed: {}",
+            self.index, self.error
+        )
+    }
+}
+
+impl std::error::Error for SequenceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<SequenceError> for Error {
+    fn from(err: SequenceError) -> Error {
+        err.error
+    }
+}
+
 #[doc(hidden)]
 pub(crate) fn from_libusb(err: i32) -> Error {
     match err {
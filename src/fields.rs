@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 use libc::c_int;
 use libusb1_sys::constants::*;
 
@@ -30,6 +32,73 @@ pub enum Speed {
     SuperPlus,
 }
 
+impl fmt::Display for Speed {
+    /// Formats the speed as its lowercase name, e.g. `high` or `superplus`, the form accepted by
+    /// its `FromStr` implementation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Speed::Unknown => "unknown",
+            Speed::Low => "low",
+            Speed::Full => "full",
+            Speed::High => "high",
+            Speed::Super => "super",
+            Speed::SuperPlus => "superplus",
+        })
+    }
+}
+
+impl FromStr for Speed {
+    type Err = ParseFieldError;
+
+    /// Parses the name of a speed, ignoring case.
+    ///
+    /// ```
+    /// use rusb::Speed;
+    ///
+    /// assert_eq!("High".parse(), Ok(Speed::High));
+    /// assert_eq!("superplus".parse(), Ok(Speed::SuperPlus));
+    /// assert!("fast".parse::<Speed>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, ParseFieldError> {
+        [
+            Speed::Unknown,
+            Speed::Low,
+            Speed::Full,
+            Speed::High,
+            Speed::Super,
+            Speed::SuperPlus,
+        ]
+        .iter()
+        .copied()
+        .find(|speed| speed.to_string().eq_ignore_ascii_case(s))
+        .ok_or_else(|| ParseFieldError::new("USB speed", s))
+    }
+}
+
+/// The error returned when parsing a [`Speed`] or [`Version`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFieldError {
+    field: &'static str,
+    input: String,
+}
+
+impl ParseFieldError {
+    fn new(field: &'static str, input: &str) -> Self {
+        ParseFieldError {
+            field,
+            input: input.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.field, self.input)
+    }
+}
+
+impl std::error::Error for ParseFieldError {}
+
 #[doc(hidden)]
 pub(crate) fn speed_from_libusb(n: c_int) -> Speed {
     match n {
@@ -139,6 +208,51 @@ pub enum Recipient {
     Other,
 }
 
+/// The setup packet of a control transfer.
+///
+/// The fields hold `bmRequestType`, `bRequest`, `wValue`, `wIndex`, and `wLength` in host-endian
+/// byte order. Unlike [`DeviceHandle::read_control`](crate::DeviceHandle::read_control) and
+/// [`DeviceHandle::write_control`](crate::DeviceHandle::write_control), which take `wLength`
+/// from the length of the buffer, `length` is stated explicitly and the buffer is checked
+/// against it by [`DeviceHandle::control_transfer`](crate::DeviceHandle::control_transfer).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControlSetup {
+    /// The `bmRequestType` field, e.g. built with [`request_type`].
+    pub request_type: u8,
+
+    /// The `bRequest` field.
+    pub request: u8,
+
+    /// The `wValue` field.
+    pub value: u16,
+
+    /// The `wIndex` field.
+    pub index: u16,
+
+    /// The `wLength` field: the number of bytes in the data stage.
+    pub length: u16,
+}
+
+/// The status of a device, as returned by a standard `GET_STATUS` request.
+///
+/// Read with [`DeviceHandle::device_status`](crate::DeviceHandle::device_status).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceStatus(pub u16);
+
+impl DeviceStatus {
+    /// Returns true if the device is currently self-powered.
+    pub fn self_powered(self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    /// Returns true if the device is currently allowed to signal a remote wakeup.
+    pub fn remote_wakeup(self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+}
+
 /// A three-part version consisting of major, minor, and sub minor components.
 ///
 /// This can be used to represent versions of the format `J.M.N`, where `J` is the major version,
@@ -194,14 +308,70 @@ impl Version {
         let Version(_, _, sub_minor) = self;
         sub_minor
     }
+
+    /// Returns true if the version is at least `major.minor`, e.g. whether a `bcdUSB` of the
+    /// device descriptor declares USB 3.0 or later.
+    ///
+    /// This is the same as comparing with `Version(major, minor, 0)`, which `Version` supports
+    /// as well.
+    ///
+    /// ```
+    /// let usb_version = rusb::Version::from_bcd(0x0320);
+    /// assert!(usb_version.supports_usb(3, 0));
+    /// assert!(!usb_version.supports_usb(3, 3));
+    /// ```
+    pub fn supports_usb(self, major: u8, minor: u8) -> bool {
+        self >= Version(major, minor, 0)
+    }
+
+    /// Formats the version the way `lsusb` shows BCD fields, with the minor and sub minor
+    /// versions as a two-digit fraction, e.g. `2.00` or `1.10`.
+    ///
+    /// ```
+    /// assert_eq!(rusb::Version::from_bcd(0x0320).to_lsusb_string(), "3.20");
+    /// ```
+    pub fn to_lsusb_string(self) -> String {
+        format!("{}.{}{}", self.major(), self.minor(), self.sub_minor())
+    }
 }
 
-impl std::fmt::Display for Version {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}.{}.{}", self.major(), self.minor(), self.sub_minor())
     }
 }
 
+impl FromStr for Version {
+    type Err = ParseFieldError;
+
+    /// Parses a version written as `major.minor.sub_minor`, the form produced by `Display`. The
+    /// sub minor version may be left out, in which case it is 0.
+    ///
+    /// ```
+    /// use rusb::Version;
+    ///
+    /// assert_eq!("2.1.0".parse(), Ok(Version(2, 1, 0)));
+    /// assert_eq!("3.2".parse(), Ok(Version(3, 2, 0)));
+    /// assert!("2.0.0.1".parse::<Version>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, ParseFieldError> {
+        let error = || ParseFieldError::new("version", s);
+
+        let mut parts = s
+            .split('.')
+            .map(|part| part.parse::<u8>().map_err(|_| error()));
+        let major = parts.next().ok_or_else(error)??;
+        let minor = parts.next().ok_or_else(error)??;
+        let sub_minor = parts.next().transpose()?.unwrap_or(0);
+
+        if parts.next().is_some() {
+            return Err(error());
+        }
+
+        Ok(Version(major, minor, sub_minor))
+    }
+}
+
 /// Builds a value for the `bmRequestType` field of a control transfer setup packet.
 ///
 /// The `bmRequestType` field of a USB control transfer setup packet is a bit field specifying
@@ -244,10 +414,103 @@ pub const fn request_type(
     value
 }
 
+/// Builds the `wValue` field of a `GET_DESCRIPTOR` or `SET_DESCRIPTOR` request.
+///
+/// The descriptor type goes into the high byte and the descriptor index into the low byte.
+///
+/// ## Examples
+///
+/// ```
+/// // the second string descriptor
+/// assert_eq!(rusb::descriptor_value(rusb::constants::LIBUSB_DT_STRING, 2), 0x0302);
+/// ```
+pub const fn descriptor_value(desc_type: u8, index: u8) -> u16 {
+    (desc_type as u16) << 8 | index as u16
+}
+
+/// Builds the `wIndex` field of a request addressed to the interface `iface`.
+///
+/// The interface number goes into the low byte; the high byte is zero for standard requests,
+/// though some class specifications put an entity ID there.
+///
+/// ## Examples
+///
+/// ```
+/// assert_eq!(rusb::interface_index(3), 0x0003);
+/// ```
+pub const fn interface_index(iface: u8) -> u16 {
+    iface as u16
+}
+
+/// Reads a little-endian `u16` from the first two bytes of `bytes`.
+///
+/// Multi-byte fields of USB descriptors are little-endian on the wire. The descriptor structs
+/// returned by libusb already hold them in host byte order, but fields read from raw descriptor
+/// bytes, such as the extra bytes of a descriptor, have to be converted with this function.
+///
+/// ## Panics
+///
+/// Panics if `bytes` is shorter than two bytes.
+///
+/// ## Examples
+///
+/// ```
+/// // wTotalLength of a configuration descriptor
+/// let config = [0x09, 0x02, 0x22, 0x01, 0x01, 0x01, 0x00, 0xA0, 0x32];
+/// assert_eq!(rusb::le_u16(&config[2..]), 0x0122);
+/// ```
+pub fn le_u16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+/// Reads a little-endian `u16` at `offset` in `bytes`, or returns `None` if `bytes` ends before
+/// the field does.
+///
+/// See [`le_u16`] for when to use this function.
+pub fn le_u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    bytes.get(offset..offset.checked_add(2)?).map(le_u16)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    // le_u16
+
+    #[test]
+    fn le_u16_reads_little_endian() {
+        assert_eq!(0x0200, le_u16(&[0x00, 0x02]));
+        assert_eq!(0x1234, le_u16(&[0x34, 0x12, 0xFF]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn le_u16_panics_on_short_input() {
+        le_u16(&[0x01]);
+    }
+
+    #[test]
+    fn le_u16_at_reads_at_offset() {
+        let bytes = [0x09, 0x02, 0x22, 0x01];
+        assert_eq!(Some(0x0122), le_u16_at(&bytes, 2));
+        assert_eq!(Some(0x2202), le_u16_at(&bytes, 1));
+        assert_eq!(None, le_u16_at(&bytes, 3));
+        assert_eq!(None, le_u16_at(&bytes, usize::MAX));
+    }
+
+    // DeviceStatus
+
+    #[test]
+    fn device_status_decodes_bits() {
+        assert!(!DeviceStatus(0x0000).self_powered());
+        assert!(!DeviceStatus(0x0000).remote_wakeup());
+        assert!(DeviceStatus(0x0001).self_powered());
+        assert!(!DeviceStatus(0x0001).remote_wakeup());
+        assert!(!DeviceStatus(0x0002).self_powered());
+        assert!(DeviceStatus(0x0002).remote_wakeup());
+        assert!(DeviceStatus(0x0003).self_powered() && DeviceStatus(0x0003).remote_wakeup());
+    }
+
     // Version
 
     #[test]
@@ -295,11 +558,28 @@ mod test {
         assert_eq!(Version(12, 3, 4), Version::from_bcd(0x1234));
     }
 
+    #[test]
+    fn version_supports_usb_from_minimum() {
+        assert!(Version::from_bcd(0x0300).supports_usb(3, 0));
+        assert!(Version::from_bcd(0x0210).supports_usb(2, 0));
+        assert!(Version::from_bcd(0x0201).supports_usb(2, 0));
+        assert!(!Version::from_bcd(0x0200).supports_usb(2, 1));
+        assert!(!Version::from_bcd(0x0110).supports_usb(2, 0));
+    }
+
     #[test]
     fn version_display() {
         assert_eq!(Version(2, 45, 13).to_string(), "2.45.13");
     }
 
+    #[test]
+    fn version_lsusb_string() {
+        assert_eq!(Version::from_bcd(0x0200).to_lsusb_string(), "2.00");
+        assert_eq!(Version::from_bcd(0x0110).to_lsusb_string(), "1.10");
+        assert_eq!(Version::from_bcd(0x0321).to_lsusb_string(), "3.21");
+        assert_eq!(Version::from_bcd(0x1000).to_lsusb_string(), "10.00");
+    }
+
     #[test]
     fn version_ord() {
         assert!(Version(0, 0, 0) < Version(1, 2, 3));
@@ -310,6 +590,59 @@ mod test {
         assert!(Version(0, 255, 0) > Version(0, 254, 255));
     }
 
+    #[test]
+    fn version_round_trips_through_string() {
+        for version in [Version(0, 0, 0), Version(2, 1, 0), Version(255, 9, 15)].iter() {
+            assert_eq!(version.to_string().parse(), Ok(*version));
+        }
+    }
+
+    #[test]
+    fn version_rejects_malformed_strings() {
+        for input in ["", "2", "2.", "2.0.0.0", "256.0", "a.b", " 2.0"].iter() {
+            let error = input.parse::<Version>().unwrap_err();
+            assert_eq!(error.to_string(), format!("invalid version: {:?}", input));
+        }
+    }
+
+    // Speed
+
+    #[test]
+    fn speed_round_trips_through_string() {
+        for speed in [
+            Speed::Unknown,
+            Speed::Low,
+            Speed::Full,
+            Speed::High,
+            Speed::Super,
+            Speed::SuperPlus,
+        ]
+        .iter()
+        {
+            assert_eq!(speed.to_string().parse(), Ok(*speed));
+            assert_eq!(speed.to_string().to_uppercase().parse(), Ok(*speed));
+        }
+    }
+
+    #[test]
+    fn speed_rejects_unknown_names() {
+        let error = "super-plus".parse::<Speed>().unwrap_err();
+        assert_eq!(error.to_string(), "invalid USB speed: \"super-plus\"");
+    }
+
+    // descriptor_value and interface_index
+
+    #[test]
+    fn descriptor_value_puts_type_in_high_byte() {
+        assert_eq!(descriptor_value(LIBUSB_DT_CONFIG, 0), 0x0200);
+        assert_eq!(descriptor_value(LIBUSB_DT_STRING, 0xEE), 0x03EE);
+    }
+
+    #[test]
+    fn interface_index_puts_interface_in_low_byte() {
+        assert_eq!(interface_index(0xFF), 0x00FF);
+    }
+
     // request_type for direction
 
     #[test]
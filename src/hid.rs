@@ -0,0 +1,113 @@
+use std::{borrow::Cow, time::Duration};
+
+use crate::{
+    device_handle::DeviceHandle,
+    fields::{interface_index, request_type, Direction, Recipient, RequestType},
+    UsbContext,
+};
+
+/// `bRequest` of the HID class request `SET_REPORT`.
+const HID_SET_REPORT: u8 = 0x09;
+
+/// Report type of feature reports, the high byte of `wValue` of `SET_REPORT`.
+const HID_REPORT_TYPE_FEATURE: u8 = 0x03;
+
+/// Returns the bytes of a report as sent to the device: `data` preceded by `report_id`, or
+/// `data` alone if `report_id` is 0.
+fn with_report_id(report_id: u8, data: &[u8]) -> Cow<'_, [u8]> {
+    match report_id {
+        0 => Cow::Borrowed(data),
+        id => {
+            let mut report = Vec::with_capacity(data.len() + 1);
+            report.push(id);
+            report.extend_from_slice(data);
+            Cow::Owned(report)
+        }
+    }
+}
+
+/// Requests of the HID class (Device Class Definition for HID 1.11).
+///
+/// A device that declares report IDs in its report descriptor expects each report to start
+/// with its ID, while a device that doesn't declare any expects the report data alone. These
+/// functions take the ID separately and add it where needed; pass 0 for a device without report
+/// IDs. This is the same convention as the HID APIs of the operating systems use.
+impl<T: UsbContext> DeviceHandle<T> {
+    /// Sends an output report over the interrupt OUT endpoint `endpoint` of a HID interface.
+    ///
+    /// `data` is the report without its ID. Unless `report_id` is 0, it is sent preceded by
+    /// `report_id`. Returns the number of bytes of `data` that were written.
+    ///
+    /// A `report_id` of 0 is not sent: it stands for a device without report IDs, which expects
+    /// `data` alone, as in hidapi and the HID APIs of the operating systems. Sending a 0 byte
+    /// first would shift the report by one byte on such a device.
+    ///
+    /// Not every HID interface has an interrupt OUT endpoint; output reports to those without
+    /// one go over the control pipe with `SET_REPORT` instead.
+    ///
+    /// ## Errors
+    ///
+    /// The errors are the same as for [`write_interrupt`](#method.write_interrupt). A report
+    /// that doesn't match what the device expects, e.g. because of a missing report ID, usually
+    /// makes the device stall the endpoint, which is reported as `Pipe`.
+    pub fn write_hid_output_report(
+        &self,
+        endpoint: u8,
+        report_id: u8,
+        data: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        let report = with_report_id(report_id, data);
+        let written = self.write_interrupt(endpoint, &report, timeout)?;
+
+        Ok(written.saturating_sub(report.len() - data.len()))
+    }
+
+    /// Sends a feature report to the HID interface `iface` with `SET_REPORT`.
+    ///
+    /// `data` is the report without its ID; unless `report_id` is 0, it is sent preceded by
+    /// `report_id`, and the ID is also given in the low byte of `wValue`. Returns the number of
+    /// bytes of `data` that were written.
+    ///
+    /// As with [`write_hid_output_report`](#method.write_hid_output_report), a `report_id` of 0
+    /// means the device has no report IDs: no ID byte is sent and the low byte of `wValue` is 0.
+    ///
+    /// ## Errors
+    ///
+    /// Devices stall requests for reports they don't have, which is reported as `Pipe`.
+    /// Otherwise the errors are the same as for [`write_control`](#method.write_control).
+    pub fn send_feature_report(
+        &self,
+        iface: u8,
+        report_id: u8,
+        data: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        let report = with_report_id(report_id, data);
+        let written = self.write_control(
+            request_type(Direction::Out, RequestType::Class, Recipient::Interface),
+            HID_SET_REPORT,
+            u16::from(HID_REPORT_TYPE_FEATURE) << 8 | u16::from(report_id),
+            interface_index(iface),
+            &report,
+            timeout,
+        )?;
+
+        Ok(written.saturating_sub(report.len() - data.len()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::with_report_id;
+
+    #[test]
+    fn it_prefixes_nonzero_report_ids() {
+        assert_eq!(&*with_report_id(0x02, &[0xAA, 0xBB]), &[0x02, 0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn it_sends_reports_without_id_as_is() {
+        assert_eq!(&*with_report_id(0, &[0xAA, 0xBB]), &[0xAA, 0xBB]);
+    }
+}
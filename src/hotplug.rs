@@ -6,14 +6,21 @@ use crate::ffi::{
     libusb_context, libusb_device, libusb_hotplug_callback_handle,
     libusb_hotplug_deregister_callback, libusb_hotplug_event, libusb_hotplug_register_callback,
 };
-use crate::{error, Device, UsbContext};
+use crate::{error, Context, Device, DeviceDescriptor, Speed, UsbContext};
 use std::{
     borrow::Borrow,
     ffi::c_void,
     fmt::{self, Debug},
     os::raw::c_int,
+    sync::{mpsc, Condvar, Mutex},
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
 };
 
+/// How long a callback must go without events before [method@Hotplug::events_settled] is
+/// called.
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
 /// When handling a [method@Hotplug::device_arrived] event it is considered safe to call
 /// any `rusb` function that takes a [`Device`]. It also safe to open a device and
 /// submit **asynchronous** transfers.
@@ -22,12 +29,151 @@ use std::{
 /// the blocking functions that retrieve various USB descriptors.
 /// These functions must be used outside of the context of the [Hotplug] functions.
 ///
+/// The `Device` methods that only read what `libusb` recorded at enumeration, and so do no
+/// I/O, are [`device_descriptor`], [`config_descriptor`], [`speed`], [`bus_number`],
+/// [`address`], [`port_number`], [`port_numbers`] and [`port_path`]. [`HotplugArrival`]
+/// bundles the ones most handlers filter on.
+///
 /// [`Device`]: crate::Device
 /// [`DeviceHandle`]: crate::DeviceHandle
 /// [`Context::unregister_callback`]: method@crate::Context::unregister_callback
+/// [`device_descriptor`]: crate::Device::device_descriptor
+/// [`config_descriptor`]: crate::Device::config_descriptor
+/// [`speed`]: crate::Device::speed
+/// [`bus_number`]: crate::Device::bus_number
+/// [`address`]: crate::Device::address
+/// [`port_number`]: crate::Device::port_number
+/// [`port_numbers`]: crate::Device::port_numbers
+/// [`port_path`]: crate::Device::port_path
 pub trait Hotplug<T: UsbContext>: Send {
     fn device_arrived(&mut self, device: Device<T>);
     fn device_left(&mut self, device: Device<T>);
+
+    /// Called once after a burst of [method@Hotplug::device_arrived] and
+    /// [method@Hotplug::device_left] events has been delivered.
+    ///
+    /// A burst ends when the callback received no further event for 100 ms. This is invoked
+    /// from the first event handling after that, e.g. a call to [`UsbContext::handle_events`].
+    /// [`UsbContext::next_timeout`] accounts for the pending delay, and waiting in
+    /// `handle_events` is cut short to it, so an event loop wakes up in time. Expensive work
+    /// such as rescanning the bus is better done here than once per device.
+    ///
+    /// The same restrictions as for the other methods apply. Other registrations may be
+    /// dropped from inside this method, but not the [Registration] of this callback.
+    fn events_settled(&mut self) {}
+}
+
+/// A [`Hotplug`] handler that also receives the context the callback was registered on.
+///
+/// Register it with [`HotplugBuilder::register_with_context`]. Having the context lets a
+/// handler look beyond the single device of an event, e.g. to keep a complete inventory of the
+/// connected devices.
+///
+/// Inside these methods, calling [`devices`](UsbContext::devices) and the other functions that
+/// only enumerate devices is permitted, as is everything permitted for [`Hotplug`]: any
+/// function that takes a [`Device`], opening a device, and registering or dropping other
+/// callbacks. Event handling functions such as [`handle_events`](UsbContext::handle_events)
+/// must not be called, since the methods run from within event handling; this also rules out
+/// synchronous transfers and the blocking descriptor reads of a `DeviceHandle`.
+pub trait HotplugWithContext<T: UsbContext>: Send {
+    fn device_arrived(&mut self, context: &T, device: Device<T>);
+    fn device_left(&mut self, context: &T, device: Device<T>);
+
+    /// Called once after a burst of events has been delivered, see
+    /// [method@Hotplug::events_settled].
+    fn events_settled(&mut self, _context: &T) {}
+}
+
+/// Adapts a [`HotplugWithContext`] handler to [`Hotplug`] by holding the context.
+struct WithContext<T: UsbContext> {
+    context: T,
+    handler: Box<dyn HotplugWithContext<T>>,
+}
+
+impl<T: UsbContext> Hotplug<T> for WithContext<T> {
+    fn device_arrived(&mut self, device: Device<T>) {
+        self.handler.device_arrived(&self.context, device);
+    }
+
+    fn device_left(&mut self, device: Device<T>) {
+        self.handler.device_left(&self.context, device);
+    }
+
+    fn events_settled(&mut self) {
+        self.handler.events_settled(&self.context);
+    }
+}
+
+/// A device that arrived, together with what handlers usually check to decide whether they
+/// care about it.
+///
+/// Build it with [`HotplugArrival::new`] in [method@Hotplug::device_arrived]; it only reads
+/// data `libusb` already has, so it is safe to do there.
+///
+/// ```no_run
+/// use rusb::{Context, Device, Hotplug, HotplugArrival, Speed};
+///
+/// struct HighSpeedOnly;
+///
+/// impl Hotplug<Context> for HighSpeedOnly {
+///     fn device_arrived(&mut self, device: Device<Context>) {
+///         let arrival = match HotplugArrival::new(device) {
+///             Ok(arrival) => arrival,
+///             Err(_) => return,
+///         };
+///         if arrival.speed() == Speed::High {
+///             println!("{:04x} connected", arrival.descriptor().product_id());
+///         }
+///     }
+///
+///     fn device_left(&mut self, _device: Device<Context>) {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HotplugArrival<T: UsbContext> {
+    device: Device<T>,
+    descriptor: DeviceDescriptor,
+    speed: Speed,
+}
+
+impl<T: UsbContext> HotplugArrival<T> {
+    /// Reads the device descriptor and speed of `device`.
+    pub fn new(device: Device<T>) -> crate::Result<Self> {
+        let descriptor = device.device_descriptor()?;
+        let speed = device.speed();
+
+        Ok(HotplugArrival {
+            device,
+            descriptor,
+            speed,
+        })
+    }
+
+    /// Returns the device that arrived.
+    pub fn device(&self) -> &Device<T> {
+        &self.device
+    }
+
+    /// Returns the device that arrived, consuming the arrival.
+    pub fn into_device(self) -> Device<T> {
+        self.device
+    }
+
+    /// Returns the device descriptor.
+    pub fn descriptor(&self) -> &DeviceDescriptor {
+        &self.descriptor
+    }
+
+    /// Returns the speed the device is connected at.
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    /// Returns the device class code from the device descriptor. Devices that declare their
+    /// class per interface report 0 here.
+    pub fn class_code(&self) -> u8 {
+        self.descriptor.class_code()
+    }
 }
 
 #[derive(Debug)]
@@ -48,6 +194,156 @@ impl<T: UsbContext> Drop for Registration<T> {
         unsafe {
             libusb_hotplug_deregister_callback(self.call_back.context.as_raw(), self.get_handle())
         }
+        let user_data = &mut *self.call_back as *mut CallbackData<T> as *mut c_void;
+        let mut pending = match PENDING.lock() {
+            Ok(pending) => pending,
+            Err(_) => return,
+        };
+        pending
+            .unsettled
+            .retain(|entry| entry.user_data != user_data);
+
+        // If another thread is about to call `events_settled` on this callback, wait until it
+        // is done. On this thread, the callback is simply skipped.
+        let current = thread::current().id();
+        loop {
+            let settling = pending
+                .settling
+                .iter()
+                .position(|&(data, _)| data == user_data);
+            match settling {
+                Some(index) if pending.settling[index].1 == current => {
+                    pending.settling.remove(index);
+                }
+                Some(_) => {
+                    pending = match SETTLED.wait(pending) {
+                        Ok(pending) => pending,
+                        Err(_) => return,
+                    };
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// A callback that received events which haven't been followed by
+/// [method@Hotplug::events_settled] yet.
+struct Unsettled {
+    context: *mut libusb_context,
+    user_data: *mut c_void,
+    settle: unsafe fn(*mut c_void),
+    last_event: Instant,
+}
+
+/// The callbacks waiting for [method@Hotplug::events_settled].
+struct Pending {
+    unsettled: Vec<Unsettled>,
+    /// Callbacks taken out of `unsettled` that a thread is calling, or about to call, without
+    /// holding the lock.
+    settling: Vec<(*mut c_void, ThreadId)>,
+}
+
+unsafe impl Send for Pending {}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    unsettled: Vec::new(),
+    settling: Vec::new(),
+});
+
+/// Signalled whenever a thread is done calling the callbacks it took out of [`PENDING`].
+static SETTLED: Condvar = Condvar::new();
+
+unsafe fn settle_callback<T: UsbContext>(user_data: *mut c_void) {
+    let reg = &mut *(user_data as *mut CallbackData<T>);
+    reg.hotplug.events_settled();
+}
+
+/// Records that the callback `user_data` on `context` received an event.
+fn record_event<T: UsbContext>(context: *mut libusb_context, user_data: *mut c_void) {
+    let mut pending = match PENDING.lock() {
+        Ok(pending) => pending,
+        Err(_) => return,
+    };
+
+    let now = Instant::now();
+    match pending
+        .unsettled
+        .iter_mut()
+        .find(|entry| entry.user_data == user_data)
+    {
+        Some(entry) => entry.last_event = now,
+        None => pending.unsettled.push(Unsettled {
+            context,
+            user_data,
+            settle: settle_callback::<T>,
+            last_event: now,
+        }),
+    }
+}
+
+/// Returns how long until the next callback registered on `context` is due for
+/// [method@Hotplug::events_settled], if any is waiting.
+pub(crate) fn settle_timeout(context: *mut libusb_context) -> Option<Duration> {
+    let pending = PENDING.lock().ok()?;
+    let now = Instant::now();
+
+    pending
+        .unsettled
+        .iter()
+        .filter(|entry| entry.context == context)
+        .map(|entry| (entry.last_event + SETTLE_DELAY).saturating_duration_since(now))
+        .min()
+}
+
+/// Calls [method@Hotplug::events_settled] for every callback registered on `context` that
+/// received no events for [`SETTLE_DELAY`] since its last one.
+pub(crate) fn settle_events(context: *mut libusb_context) {
+    let current = thread::current().id();
+    let due = {
+        let mut pending = match PENDING.lock() {
+            Ok(pending) => pending,
+            Err(_) => return,
+        };
+
+        let now = Instant::now();
+        let mut due = Vec::new();
+        pending.unsettled.retain(|entry| {
+            if entry.context != context || now < entry.last_event + SETTLE_DELAY {
+                return true;
+            }
+            due.push((entry.user_data, entry.settle));
+            false
+        });
+        if due.is_empty() {
+            return;
+        }
+        let settling = due.iter().map(|&(user_data, _)| (user_data, current));
+        pending.settling.extend(settling);
+        due
+    };
+
+    // The callbacks run without the lock held, so that they can drop registrations. A
+    // registration dropped meanwhile on another thread waits in `Registration::drop` until its
+    // callback is no longer in `settling`.
+    for (user_data, settle) in due {
+        let still_registered = match PENDING.lock() {
+            Ok(pending) => pending
+                .settling
+                .iter()
+                .any(|&(data, thread)| data == user_data && thread == current),
+            Err(_) => false,
+        };
+        if still_registered {
+            let _ = std::panic::catch_unwind(|| unsafe { settle(user_data) });
+        }
+
+        if let Ok(mut pending) = PENDING.lock() {
+            pending
+                .settling
+                .retain(|&(data, thread)| !(data == user_data && thread == current));
+        }
+        SETTLED.notify_all();
     }
 }
 
@@ -119,6 +415,12 @@ impl HotplugBuilder {
     /// the blocking functions that retrieve various USB descriptors.
     /// These functions must be used outside of the context of the [Hotplug] functions.
     ///
+    /// The callback is called from within event handling, on whichever thread handles the
+    /// events of `context`, e.g. with [`handle_events`](UsbContext::handle_events). If no thread
+    /// does, the callback never fires. A context opened with [`Context::with_event_thread`]
+    /// handles its events on a thread of its own. To process the events on a particular thread
+    /// instead, register with [`register_channel`](#method.register_channel).
+    ///
     /// [`Device`]: crate::Device
     /// [`DeviceHandle`]: crate::DeviceHandle
     /// [`Context::unregister_callback`]: method@crate::Context::unregister_callback
@@ -166,6 +468,163 @@ impl HotplugBuilder {
             Ok(Registration { handle, call_back })
         }
     }
+
+    /// Registers a `callback` that receives the context along with each event.
+    ///
+    /// This is the same as [`register`](#method.register), for a [`HotplugWithContext`]
+    /// handler.
+    pub fn register_with_context<U: UsbContext + 'static, T: Borrow<U>>(
+        self,
+        context: T,
+        callback: Box<dyn HotplugWithContext<U>>,
+    ) -> crate::Result<Registration<U>> {
+        let handler = WithContext {
+            context: context.borrow().clone(),
+            handler: callback,
+        };
+
+        self.register(context, Box::new(handler))
+    }
+
+    /// Registers a callback that forwards the events to the returned channel.
+    ///
+    /// The events still originate from event handling on `context`, so some thread has to
+    /// handle its events, but they can be received and processed on any thread, which is also
+    /// free to make synchronous transfers. The channel is unbounded. The callback is
+    /// deregistered when the [Registration] is dropped, after which the receiver reports a
+    /// disconnection once the remaining events are taken.
+    ///
+    /// ```no_run
+    /// use rusb::{Context, HotplugBuilder, HotplugEvent};
+    ///
+    /// let context = Context::with_event_thread()?;
+    /// let (_registration, events) = HotplugBuilder::new()
+    ///     .enumerate(true)
+    ///     .register_channel(&context)?;
+    ///
+    /// for event in events {
+    ///     match event {
+    ///         HotplugEvent::Arrived(device) => println!("arrived: {}", device),
+    ///         HotplugEvent::Left(device) => println!("left: {}", device),
+    ///     }
+    /// }
+    /// # Ok::<(), rusb::Error>(())
+    /// ```
+    pub fn register_channel<U: UsbContext + 'static>(
+        self,
+        context: &U,
+    ) -> crate::Result<(Registration<U>, mpsc::Receiver<HotplugEvent<U>>)> {
+        let (sender, receiver) = mpsc::channel();
+        let registration = self.register::<U, _>(context, Box::new(ChannelHotplug { sender }))?;
+
+        Ok((registration, receiver))
+    }
+}
+
+/// A hotplug event, as delivered by [`HotplugBuilder::register_channel`].
+#[derive(Debug, Clone)]
+pub enum HotplugEvent<T: UsbContext> {
+    /// The device was connected, or already present if the registration enumerates devices.
+    Arrived(Device<T>),
+
+    /// The device was disconnected.
+    Left(Device<T>),
+}
+
+/// Forwards events to a channel for [`HotplugBuilder::register_channel`].
+struct ChannelHotplug<T: UsbContext> {
+    sender: mpsc::Sender<HotplugEvent<T>>,
+}
+
+impl<T: UsbContext> Hotplug<T> for ChannelHotplug<T> {
+    // Sending fails once the receiver is gone, which leaves nobody to tell.
+    fn device_arrived(&mut self, device: Device<T>) {
+        let _ = self.sender.send(HotplugEvent::Arrived(device));
+    }
+
+    fn device_left(&mut self, device: Device<T>) {
+        let _ = self.sender.send(HotplugEvent::Left(device));
+    }
+}
+
+/// A [`Context`] together with the hotplug callbacks registered on it.
+///
+/// Callbacks stop as soon as their [Registration] is dropped, so registrations have to be kept
+/// somewhere. This type keeps them for as long as it lives, which saves declaring a field of
+/// type `Option<Registration<_>>` per callback.
+///
+/// ```no_run
+/// use rusb::{Context, ContextHotplug, Device, Hotplug, HotplugBuilder, UsbContext};
+///
+/// struct Printer;
+///
+/// impl Hotplug<Context> for Printer {
+///     fn device_arrived(&mut self, device: Device<Context>) {
+///         println!("arrived: {}", device);
+///     }
+///
+///     fn device_left(&mut self, device: Device<Context>) {
+///         println!("left: {}", device);
+///     }
+/// }
+///
+/// # fn main() -> rusb::Result<()> {
+/// let mut hotplug = ContextHotplug::new(Context::new()?);
+/// hotplug.register(HotplugBuilder::new().enumerate(true), Box::new(Printer))?;
+///
+/// loop {
+///     hotplug.context().handle_events(None)?;
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ContextHotplug {
+    context: Context,
+    registrations: Vec<Registration<Context>>,
+}
+
+impl ContextHotplug {
+    /// Creates a holder for the hotplug callbacks of `context`, with no callbacks yet.
+    pub fn new(context: Context) -> Self {
+        ContextHotplug {
+            context,
+            registrations: Vec::new(),
+        }
+    }
+
+    /// Returns the context the callbacks are registered on, e.g. to handle its events.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Registers `callback` with the filters of `builder`, see [`HotplugBuilder::register`].
+    ///
+    /// The callback stays registered until this is dropped or
+    /// [`unregister_all`](#method.unregister_all) is called.
+    pub fn register(
+        &mut self,
+        builder: &HotplugBuilder,
+        callback: Box<dyn Hotplug<Context>>,
+    ) -> crate::Result<()> {
+        let registration = builder.register(&self.context, callback)?;
+        self.registrations.push(registration);
+        Ok(())
+    }
+
+    /// Returns the number of registered callbacks.
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    /// Returns true if no callbacks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+
+    /// Deregisters all callbacks.
+    pub fn unregister_all(&mut self) {
+        self.registrations.clear();
+    }
 }
 
 struct CallbackData<T: UsbContext> {
@@ -198,11 +657,13 @@ pub extern "system" fn hotplug_callback<T: UsbContext>(
                 std::ptr::NonNull::new_unchecked(device),
             )
         };
+        let context = reg.context.as_raw();
         match event {
             LIBUSB_HOTPLUG_EVENT_DEVICE_ARRIVED => reg.hotplug.device_arrived(device),
             LIBUSB_HOTPLUG_EVENT_DEVICE_LEFT => reg.hotplug.device_left(device),
-            _ => (),
+            _ => return,
         };
+        record_event::<T>(context, user_data);
     });
     match ret {
         Ok(_) => 0,
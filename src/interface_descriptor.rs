@@ -1,8 +1,17 @@
 use std::{fmt, slice};
 
-use libusb1_sys::{libusb_endpoint_descriptor, libusb_interface, libusb_interface_descriptor};
+use libusb1_sys::{
+    constants::*, libusb_endpoint_descriptor, libusb_interface, libusb_interface_descriptor,
+};
 
-use crate::endpoint_descriptor::{self, EndpointDescriptor};
+use crate::{
+    endpoint_descriptor::{self, EndpointDescriptor},
+    fields::{self, Direction, Speed, TransferType, Version},
+};
+
+/// The largest `bNumEndpoints` accepted as genuine by
+/// [`InterfaceDescriptor::endpoint_descriptors`].
+pub(crate) const MAX_ENDPOINTS: u8 = 32;
 
 /// A device interface.
 ///
@@ -24,6 +33,45 @@ impl<'a> Interface<'a> {
             iter: self.descriptors.iter(),
         }
     }
+
+    /// Returns the number of alternate settings of the interface.
+    pub fn num_alt_settings(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    /// Returns the descriptor of the `n`-th alternate setting, counting from zero.
+    ///
+    /// Alternate settings are normally numbered in order, so this is the setting with
+    /// `bAlternateSetting` equal to `n`. Check
+    /// [`setting_number`](InterfaceDescriptor::setting_number) if the device may number them
+    /// differently.
+    pub fn alt_setting(&self, n: u8) -> Option<InterfaceDescriptor<'a>> {
+        self.descriptors
+            .get(usize::from(n))
+            .map(|descriptor| InterfaceDescriptor { descriptor })
+    }
+
+    /// Returns the isochronous bandwidth each alternate setting needs at `speed`, as pairs of
+    /// the setting number and the bytes per service interval.
+    ///
+    /// The bytes of a setting are the sum of
+    /// [`max_transfer_per_interval`](EndpointDescriptor::max_transfer_per_interval) over its
+    /// isochronous endpoints, which accounts for high speed transactions per microframe and
+    /// SuperSpeed bursts. Streaming classes such as UVC and UAC keep setting 0 free of
+    /// isochronous endpoints and offer increasing bandwidth in the other settings, so a driver
+    /// can pick the largest setting that fits the bandwidth it needs or has available.
+    pub fn bandwidth_per_setting(&self, speed: Speed) -> Vec<(u8, u32)> {
+        self.descriptors()
+            .map(|setting| {
+                let bytes = setting
+                    .endpoint_descriptors()
+                    .filter(|endpoint| endpoint.transfer_type() == TransferType::Isochronous)
+                    .map(|endpoint| endpoint.max_transfer_per_interval(speed))
+                    .sum();
+                (setting.setting_number(), bytes)
+            })
+            .collect()
+    }
 }
 
 /// Iterator over an interface's descriptors.
@@ -45,6 +93,8 @@ impl<'a> Iterator for InterfaceDescriptors<'a> {
     }
 }
 
+impl<'a> ExactSizeIterator for InterfaceDescriptors<'a> {}
+
 /// Describes an alternate setting for an interface.
 pub struct InterfaceDescriptor<'a> {
     descriptor: &'a libusb_interface_descriptor,
@@ -100,9 +150,16 @@ impl<'a> InterfaceDescriptor<'a> {
     }
 
     /// Returns an iterator over the interface's endpoint descriptors.
+    ///
+    /// The endpoints are read from the array `libusb` parsed, without copying, so calling this
+    /// repeatedly is cheap. An interface can't have more than 30 endpoints besides endpoint
+    /// zero, so a `bNumEndpoints` above 32, or a missing array, is taken as a corrupt descriptor
+    /// and yields no endpoints rather than reading past the array.
     pub fn endpoint_descriptors(&self) -> EndpointDescriptors<'a> {
         let endpoints = match self.descriptor.bNumEndpoints {
             0 => &[],
+            _ if self.descriptor.endpoint.is_null() => &[],
+            n if n > MAX_ENDPOINTS => &[],
             n => unsafe { slice::from_raw_parts(self.descriptor.endpoint, n as usize) },
         };
 
@@ -111,6 +168,18 @@ impl<'a> InterfaceDescriptor<'a> {
         }
     }
 
+    /// Returns an iterator over the interface's endpoint descriptors that have the given
+    /// transfer type and direction.
+    pub fn endpoints_of(
+        &self,
+        transfer_type: TransferType,
+        direction: Direction,
+    ) -> impl Iterator<Item = EndpointDescriptor<'a>> {
+        self.endpoint_descriptors().filter(move |endpoint| {
+            endpoint.transfer_type() == transfer_type && endpoint.direction() == direction
+        })
+    }
+
     /// Returns the unknown 'extra' bytes that libusb does not understand.
     pub fn extra(&self) -> &[u8] {
         unsafe {
@@ -120,6 +189,47 @@ impl<'a> InterfaceDescriptor<'a> {
             }
         }
     }
+
+    /// Returns the class-specific HID descriptor of the interface, if it has one.
+    ///
+    /// HID interfaces carry this descriptor in their [`extra`](#method.extra) bytes. It holds
+    /// the length of the report descriptor, which is needed to request it from the device.
+    pub fn hid_descriptor(&self) -> Option<HidDescriptor> {
+        parse_hid_descriptor(self.extra())
+    }
+}
+
+/// Finds and parses the first HID descriptor in the class-specific descriptors `extra`.
+fn parse_hid_descriptor(extra: &[u8]) -> Option<HidDescriptor> {
+    let mut rest = extra;
+
+    while rest.len() >= 2 {
+        let len = usize::from(rest[0]);
+        if len < 2 || len > rest.len() {
+            return None;
+        }
+        let (descriptor, next) = rest.split_at(len);
+
+        // bLength, bDescriptorType, bcdHID, bCountryCode, bNumDescriptors, then
+        // bDescriptorType and wDescriptorLength for each class descriptor
+        if descriptor[1] == LIBUSB_DT_HID && len >= 6 {
+            let class_descriptors = descriptor[6..]
+                .chunks_exact(3)
+                .take(usize::from(descriptor[5]))
+                .map(|entry| (entry[0], fields::le_u16(&entry[1..])))
+                .collect();
+
+            return Some(HidDescriptor {
+                hid_version: fields::le_u16(&descriptor[2..]),
+                country_code: descriptor[4],
+                class_descriptors,
+            });
+        }
+
+        rest = next;
+    }
+
+    None
 }
 
 impl<'a> fmt::Debug for InterfaceDescriptor<'a> {
@@ -140,6 +250,41 @@ impl<'a> fmt::Debug for InterfaceDescriptor<'a> {
     }
 }
 
+/// The class-specific descriptor of a HID interface.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HidDescriptor {
+    hid_version: u16,
+    country_code: u8,
+    class_descriptors: Vec<(u8, u16)>,
+}
+
+impl HidDescriptor {
+    /// Returns the version of the HID specification the interface complies with.
+    pub fn hid_version(&self) -> Version {
+        Version::from_bcd(self.hid_version)
+    }
+
+    /// Returns the country code of localized hardware, or 0 if the hardware is not localized.
+    pub fn country_code(&self) -> u8 {
+        self.country_code
+    }
+
+    /// Returns the type and length of each class descriptor of the interface, such as the
+    /// report descriptor.
+    pub fn class_descriptors(&self) -> &[(u8, u16)] {
+        &self.class_descriptors
+    }
+
+    /// Returns the length of the report descriptor, or `None` if the HID descriptor doesn't
+    /// list one.
+    pub fn report_descriptor_length(&self) -> Option<u16> {
+        self.class_descriptors
+            .iter()
+            .find(|&&(descriptor_type, _)| descriptor_type == LIBUSB_DT_REPORT)
+            .map(|&(_, length)| length)
+    }
+}
+
 /// Iterator over an interface's endpoint descriptors.
 pub struct EndpointDescriptors<'a> {
     iter: slice::Iter<'a, libusb_endpoint_descriptor>,
@@ -157,6 +302,8 @@ impl<'a> Iterator for EndpointDescriptors<'a> {
     }
 }
 
+impl<'a> ExactSizeIterator for EndpointDescriptors<'a> {}
+
 #[doc(hidden)]
 pub(crate) unsafe fn from_libusb(interface: &libusb_interface) -> Interface {
     let descriptors =
@@ -168,6 +315,8 @@ pub(crate) unsafe fn from_libusb(interface: &libusb_interface) -> Interface {
 
 #[cfg(test)]
 mod test {
+    use crate::fields::Version;
+
     #[test]
     fn it_has_interface_number() {
         assert_eq!(
@@ -291,4 +440,174 @@ mod test {
 
         assert_eq!(vec![0x87], endpoint_addresses);
     }
+
+    #[test]
+    fn it_ignores_corrupt_endpoint_counts() {
+        let mut descriptor = interface_descriptor!(endpoint_descriptor!(bEndpointAddress: 0x81));
+        descriptor.bNumEndpoints = 200;
+        let libusb_interface = interface!(descriptor);
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+        assert_eq!(
+            0,
+            interface
+                .descriptors()
+                .next()
+                .unwrap()
+                .endpoint_descriptors()
+                .len()
+        );
+
+        let libusb_interface = interface!(interface_descriptor!(bNumEndpoints: 2));
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+        assert_eq!(
+            0,
+            interface
+                .descriptors()
+                .next()
+                .unwrap()
+                .endpoint_descriptors()
+                .len()
+        );
+    }
+
+    #[test]
+    fn it_sums_isochronous_bandwidth_per_setting() {
+        let libusb_interface = interface!(interface_descriptor!(bAlternateSetting: 0), {
+            let mut setting = interface_descriptor!(
+                endpoint_descriptor!(bmAttributes: 0x05, wMaxPacketSize: 0x1400),
+                endpoint_descriptor!(bmAttributes: 0x03, wMaxPacketSize: 64)
+            );
+            setting.bAlternateSetting = 1;
+            setting
+        });
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+
+        assert_eq!(
+            vec![(0, 0), (1, 3072)],
+            interface.bandwidth_per_setting(crate::Speed::High)
+        );
+        assert_eq!(
+            vec![(0, 0), (1, 1024)],
+            interface.bandwidth_per_setting(crate::Speed::Full)
+        );
+    }
+
+    #[test]
+    fn it_indexes_alternate_settings() {
+        let libusb_interface = interface!(
+            interface_descriptor!(bAlternateSetting: 0),
+            interface_descriptor!(bAlternateSetting: 1)
+        );
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+
+        assert_eq!(2, interface.num_alt_settings());
+        assert_eq!(
+            Some(1),
+            interface
+                .alt_setting(1)
+                .map(|setting| setting.setting_number())
+        );
+        assert!(interface.alt_setting(2).is_none());
+    }
+
+    #[test]
+    fn it_knows_the_number_of_descriptors_left() {
+        let libusb_interface = interface!(interface_descriptor!(
+            endpoint_descriptor!(bEndpointAddress: 0x81),
+            endpoint_descriptor!(bEndpointAddress: 0x02)
+        ));
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+
+        let mut descriptors = interface.descriptors();
+        assert_eq!(1, descriptors.len());
+
+        let mut endpoints = descriptors.next().unwrap().endpoint_descriptors();
+        assert_eq!(0, descriptors.len());
+        assert_eq!(2, endpoints.len());
+
+        endpoints.next();
+        assert_eq!(1, endpoints.len());
+    }
+
+    #[test]
+    fn it_filters_endpoints_by_type_and_direction() {
+        use crate::fields::{Direction, TransferType};
+
+        let libusb_interface = interface!(interface_descriptor!(
+            endpoint_descriptor!(bEndpointAddress: 0x81, bmAttributes: 0x02),
+            endpoint_descriptor!(bEndpointAddress: 0x02, bmAttributes: 0x02),
+            endpoint_descriptor!(bEndpointAddress: 0x83, bmAttributes: 0x03),
+            endpoint_descriptor!(bEndpointAddress: 0x84, bmAttributes: 0x02)
+        ));
+        let interface = unsafe { super::from_libusb(&libusb_interface) };
+        let descriptor = interface.descriptors().next().unwrap();
+
+        let bulk_in = descriptor
+            .endpoints_of(TransferType::Bulk, Direction::In)
+            .map(|endpoint| endpoint.address())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0x81, 0x84], bulk_in);
+
+        let interrupt_out = descriptor
+            .endpoints_of(TransferType::Interrupt, Direction::Out)
+            .count();
+        assert_eq!(0, interrupt_out);
+    }
+
+    #[test]
+    fn it_parses_hid_descriptor() {
+        let extra = [
+            0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x3F, 0x00, // HID, report descriptor
+        ];
+        let hid = super::parse_hid_descriptor(&extra).unwrap();
+
+        assert_eq!(Version(1, 1, 1), hid.hid_version());
+        assert_eq!(0, hid.country_code());
+        assert_eq!(&[(0x22, 0x003F)], hid.class_descriptors());
+        assert_eq!(Some(0x003F), hid.report_descriptor_length());
+    }
+
+    #[test]
+    fn it_finds_hid_descriptor_after_other_descriptors() {
+        let extra = [
+            0x04, 0x24, 0x00, 0x00, // unrelated class-specific descriptor
+            0x0C, 0x21, 0x00, 0x02, 0x21, 0x02, 0x23, 0x10, 0x00, 0x22, 0x00, 0x01,
+        ];
+        let hid = super::parse_hid_descriptor(&extra).unwrap();
+
+        assert_eq!(Version(2, 0, 0), hid.hid_version());
+        assert_eq!(0x21, hid.country_code());
+        assert_eq!(&[(0x23, 0x0010), (0x22, 0x0100)], hid.class_descriptors());
+        assert_eq!(Some(0x0100), hid.report_descriptor_length());
+    }
+
+    #[test]
+    fn it_handles_missing_hid_descriptor() {
+        assert_eq!(None, super::parse_hid_descriptor(&[]));
+        assert_eq!(None, super::parse_hid_descriptor(&[0x04, 0x24, 0x00, 0x00]));
+        assert_eq!(None, super::parse_hid_descriptor(&[0x09, 0x21, 0x11, 0x01]));
+
+        let hid = super::parse_hid_descriptor(&[0x06, 0x21, 0x11, 0x01, 0x00, 0x00]).unwrap();
+        assert_eq!(None, hid.report_descriptor_length());
+    }
+
+    #[test]
+    fn it_reads_hid_descriptor_from_extra() {
+        let extra = [0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x3F, 0x00];
+        let interface = interface!(interface_descriptor!(
+            extra: extra.as_ptr(),
+            extra_length: extra.len() as i32
+        ));
+
+        assert_eq!(
+            Some(0x003F),
+            unsafe { super::from_libusb(&interface) }
+                .descriptors()
+                .next()
+                .unwrap()
+                .hid_descriptor()
+                .unwrap()
+                .report_descriptor_length()
+        );
+    }
 }
@@ -8,12 +8,47 @@ const SUB_LANGUAGE_MASK: u16 = 0xFC00;
 /// The dialect may be based on regional differences (United States English compared to United
 /// Kingdom English), writing systems (Cyrillic compared to Latin), or age (Modern compared to
 /// Traditional). Each primary language has its own set of sub languages.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// Languages compare equal and hash identically when their `LANGID`s are equal, so they can be
+/// used as keys in a `HashMap`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Language {
     raw: u16,
 }
 
 impl Language {
+    /// Creates a language from a primary language and a sub language.
+    ///
+    /// Returns `None` if the pair doesn't correspond to a `LANGID`, for example a sub language
+    /// that is not a dialect of `primary`.
+    ///
+    /// ```
+    /// use rusb::{Language, PrimaryLanguage, SubLanguage};
+    ///
+    /// let language = Language::new(PrimaryLanguage::English, SubLanguage::UnitedStates).unwrap();
+    /// assert_eq!(language.lang_id(), 0x0409);
+    /// ```
+    pub fn new(primary: PrimaryLanguage, sub: SubLanguage) -> Option<Language> {
+        let primary_raw = primary.to_raw();
+        if primary_raw & !PRIMARY_LANGUAGE_MASK != 0 {
+            return None;
+        }
+
+        // Sub language 0x01 is the default dialect, so prefer it when several encodings map to
+        // the same pair.
+        (1..=SUB_LANGUAGE_MASK >> 10)
+            .chain(Some(0))
+            .map(|n| Language::from_lang_id(primary_raw | n << 10))
+            .find(|language| {
+                language.primary_language() == primary && language.sub_language() == sub
+            })
+    }
+
+    /// Creates a language from its 16-bit `LANGID`.
+    pub fn from_lang_id(lang_id: u16) -> Language {
+        from_lang_id(lang_id)
+    }
+
     /// Returns the language's 16-bit `LANGID`.
     ///
     /// Each language's `LANGID` is defined by the USB forum
@@ -31,6 +66,26 @@ impl Language {
     pub fn sub_language(self) -> SubLanguage {
         SubLanguage::from_raw(self.primary_language(), self.raw)
     }
+
+    /// Returns a human-readable name for the language, such as `"English (United States)"`.
+    ///
+    /// The name is made of the names of the primary and sub language. Standard dialects are
+    /// named after the primary language alone, and unknown parts are shown as their `LANGID`.
+    ///
+    /// ```
+    /// use rusb::Language;
+    ///
+    /// assert_eq!(Language::from_lang_id(0x0409).name(), "English (United States)");
+    /// assert_eq!(Language::from_lang_id(0x0407).name(), "German");
+    /// ```
+    pub fn name(self) -> String {
+        match (self.primary_language(), self.sub_language()) {
+            (PrimaryLanguage::Other(_), _) => format!("Unknown (0x{:04X})", self.raw),
+            (primary, SubLanguage::Standard) => primary.name().to_owned(),
+            (primary, SubLanguage::Other(_)) => format!("{} (0x{:04X})", primary.name(), self.raw),
+            (primary, sub) => format!("{} ({})", primary.name(), sub.name()),
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -39,7 +94,7 @@ pub(crate) fn from_lang_id(raw: u16) -> Language {
 }
 
 /// Primary language families.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PrimaryLanguage {
     Afrikaans,
     Albanian,
@@ -199,10 +254,172 @@ impl PrimaryLanguage {
             n => PrimaryLanguage::Other(n),
         }
     }
+
+    /// Returns the primary language bits of a `LANGID`.
+    fn to_raw(self) -> u16 {
+        match self {
+            PrimaryLanguage::Afrikaans => 0x0036,
+            PrimaryLanguage::Albanian => 0x001C,
+            PrimaryLanguage::Arabic => 0x0001,
+            PrimaryLanguage::Armenian => 0x002B,
+            PrimaryLanguage::Assamese => 0x004D,
+            PrimaryLanguage::Azeri => 0x002C,
+            PrimaryLanguage::Basque => 0x002D,
+            PrimaryLanguage::Belarussian => 0x0023,
+            PrimaryLanguage::Bengali => 0x0045,
+            PrimaryLanguage::Bulgarian => 0x0002,
+            PrimaryLanguage::Burmese => 0x0055,
+            PrimaryLanguage::Catalan => 0x0003,
+            PrimaryLanguage::Chinese => 0x0004,
+            PrimaryLanguage::Croatian => 0x001A,
+            PrimaryLanguage::Czech => 0x0005,
+            PrimaryLanguage::Danish => 0x0006,
+            PrimaryLanguage::Dutch => 0x0013,
+            PrimaryLanguage::English => 0x0009,
+            PrimaryLanguage::Estonian => 0x0025,
+            PrimaryLanguage::Faeroese => 0x0038,
+            PrimaryLanguage::Farsi => 0x0029,
+            PrimaryLanguage::Finnish => 0x000B,
+            PrimaryLanguage::French => 0x000C,
+            PrimaryLanguage::Georgian => 0x0037,
+            PrimaryLanguage::German => 0x0007,
+            PrimaryLanguage::Greek => 0x0008,
+            PrimaryLanguage::Gujarati => 0x0047,
+            PrimaryLanguage::Hebrew => 0x000D,
+            PrimaryLanguage::Hindi => 0x0039,
+            PrimaryLanguage::Hungarian => 0x000E,
+            PrimaryLanguage::Icelandic => 0x000F,
+            PrimaryLanguage::Indonesian => 0x0021,
+            PrimaryLanguage::Italian => 0x0010,
+            PrimaryLanguage::Japanese => 0x0011,
+            PrimaryLanguage::Kannada => 0x004B,
+            PrimaryLanguage::Kashmiri => 0x0060,
+            PrimaryLanguage::Kazakh => 0x003F,
+            PrimaryLanguage::Konkani => 0x0057,
+            PrimaryLanguage::Korean => 0x0012,
+            PrimaryLanguage::Latvian => 0x0026,
+            PrimaryLanguage::Lithuanian => 0x0027,
+            PrimaryLanguage::Macedonian => 0x002F,
+            PrimaryLanguage::Malay => 0x003E,
+            PrimaryLanguage::Malayalam => 0x004C,
+            PrimaryLanguage::Manipuri => 0x0058,
+            PrimaryLanguage::Marathi => 0x004E,
+            PrimaryLanguage::Nepali => 0x0061,
+            PrimaryLanguage::Norwegian => 0x0014,
+            PrimaryLanguage::Oriya => 0x0048,
+            PrimaryLanguage::Polish => 0x0015,
+            PrimaryLanguage::Portuguese => 0x0016,
+            PrimaryLanguage::Punjabi => 0x0046,
+            PrimaryLanguage::Romanian => 0x0018,
+            PrimaryLanguage::Russian => 0x0019,
+            PrimaryLanguage::Sanskrit => 0x004F,
+            PrimaryLanguage::Serbian => 0x001A,
+            PrimaryLanguage::Sindhi => 0x0059,
+            PrimaryLanguage::Slovak => 0x001B,
+            PrimaryLanguage::Slovenian => 0x0024,
+            PrimaryLanguage::Spanish => 0x000A,
+            PrimaryLanguage::Sutu => 0x0030,
+            PrimaryLanguage::Swahili => 0x0041,
+            PrimaryLanguage::Swedish => 0x001D,
+            PrimaryLanguage::Tamil => 0x0049,
+            PrimaryLanguage::Tatar => 0x0044,
+            PrimaryLanguage::Telugu => 0x004A,
+            PrimaryLanguage::Thai => 0x001E,
+            PrimaryLanguage::Turkish => 0x001F,
+            PrimaryLanguage::Ukrainian => 0x0022,
+            PrimaryLanguage::Urdu => 0x0020,
+            PrimaryLanguage::Uzbek => 0x0043,
+            PrimaryLanguage::Vietnamese => 0x002A,
+            PrimaryLanguage::HID => 0x00FF,
+            PrimaryLanguage::Other(n) => n,
+        }
+    }
+
+    /// Returns the English name of the primary language, e.g. `"English"`.
+    ///
+    /// Unknown primary languages are named `"Unknown"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PrimaryLanguage::Afrikaans => "Afrikaans",
+            PrimaryLanguage::Albanian => "Albanian",
+            PrimaryLanguage::Arabic => "Arabic",
+            PrimaryLanguage::Armenian => "Armenian",
+            PrimaryLanguage::Assamese => "Assamese",
+            PrimaryLanguage::Azeri => "Azeri",
+            PrimaryLanguage::Basque => "Basque",
+            PrimaryLanguage::Belarussian => "Belarussian",
+            PrimaryLanguage::Bengali => "Bengali",
+            PrimaryLanguage::Bulgarian => "Bulgarian",
+            PrimaryLanguage::Burmese => "Burmese",
+            PrimaryLanguage::Catalan => "Catalan",
+            PrimaryLanguage::Chinese => "Chinese",
+            PrimaryLanguage::Croatian => "Croatian",
+            PrimaryLanguage::Czech => "Czech",
+            PrimaryLanguage::Danish => "Danish",
+            PrimaryLanguage::Dutch => "Dutch",
+            PrimaryLanguage::English => "English",
+            PrimaryLanguage::Estonian => "Estonian",
+            PrimaryLanguage::Faeroese => "Faeroese",
+            PrimaryLanguage::Farsi => "Farsi",
+            PrimaryLanguage::Finnish => "Finnish",
+            PrimaryLanguage::French => "French",
+            PrimaryLanguage::Georgian => "Georgian",
+            PrimaryLanguage::German => "German",
+            PrimaryLanguage::Greek => "Greek",
+            PrimaryLanguage::Gujarati => "Gujarati",
+            PrimaryLanguage::Hebrew => "Hebrew",
+            PrimaryLanguage::Hindi => "Hindi",
+            PrimaryLanguage::Hungarian => "Hungarian",
+            PrimaryLanguage::Icelandic => "Icelandic",
+            PrimaryLanguage::Indonesian => "Indonesian",
+            PrimaryLanguage::Italian => "Italian",
+            PrimaryLanguage::Japanese => "Japanese",
+            PrimaryLanguage::Kannada => "Kannada",
+            PrimaryLanguage::Kashmiri => "Kashmiri",
+            PrimaryLanguage::Kazakh => "Kazakh",
+            PrimaryLanguage::Konkani => "Konkani",
+            PrimaryLanguage::Korean => "Korean",
+            PrimaryLanguage::Latvian => "Latvian",
+            PrimaryLanguage::Lithuanian => "Lithuanian",
+            PrimaryLanguage::Macedonian => "Macedonian",
+            PrimaryLanguage::Malay => "Malay",
+            PrimaryLanguage::Malayalam => "Malayalam",
+            PrimaryLanguage::Manipuri => "Manipuri",
+            PrimaryLanguage::Marathi => "Marathi",
+            PrimaryLanguage::Nepali => "Nepali",
+            PrimaryLanguage::Norwegian => "Norwegian",
+            PrimaryLanguage::Oriya => "Oriya",
+            PrimaryLanguage::Polish => "Polish",
+            PrimaryLanguage::Portuguese => "Portuguese",
+            PrimaryLanguage::Punjabi => "Punjabi",
+            PrimaryLanguage::Romanian => "Romanian",
+            PrimaryLanguage::Russian => "Russian",
+            PrimaryLanguage::Sanskrit => "Sanskrit",
+            PrimaryLanguage::Serbian => "Serbian",
+            PrimaryLanguage::Sindhi => "Sindhi",
+            PrimaryLanguage::Slovak => "Slovak",
+            PrimaryLanguage::Slovenian => "Slovenian",
+            PrimaryLanguage::Spanish => "Spanish",
+            PrimaryLanguage::Sutu => "Sutu",
+            PrimaryLanguage::Swahili => "Swahili",
+            PrimaryLanguage::Swedish => "Swedish",
+            PrimaryLanguage::Tamil => "Tamil",
+            PrimaryLanguage::Tatar => "Tatar",
+            PrimaryLanguage::Telugu => "Telugu",
+            PrimaryLanguage::Thai => "Thai",
+            PrimaryLanguage::Turkish => "Turkish",
+            PrimaryLanguage::Ukrainian => "Ukrainian",
+            PrimaryLanguage::Urdu => "Urdu",
+            PrimaryLanguage::Uzbek => "Uzbek",
+            PrimaryLanguage::Vietnamese => "Vietnamese",
+            PrimaryLanguage::HID => "HID",
+            PrimaryLanguage::Other(_) => "Unknown",
+        }
+    }
 }
 
 /// Language dialects and writing systems.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum SubLanguage {
     Standard,
     Classic,
@@ -290,6 +507,94 @@ pub enum SubLanguage {
 }
 
 impl SubLanguage {
+    /// Returns the English name of the sub language, e.g. `"United States"`.
+    ///
+    /// Unknown sub languages are named `"Unknown"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SubLanguage::Standard => "Standard",
+            SubLanguage::Classic => "Classic",
+            SubLanguage::Traditional => "Traditional",
+            SubLanguage::Modern => "Modern",
+            SubLanguage::Algeria => "Algeria",
+            SubLanguage::Argentina => "Argentina",
+            SubLanguage::Australia => "Australia",
+            SubLanguage::Austria => "Austria",
+            SubLanguage::Bahrain => "Bahrain",
+            SubLanguage::Belgium => "Belgium",
+            SubLanguage::Belize => "Belize",
+            SubLanguage::Bokmal => "Bokmal",
+            SubLanguage::Bolivia => "Bolivia",
+            SubLanguage::Brazil => "Brazil",
+            SubLanguage::BruneiDarussalam => "Brunei Darussalam",
+            SubLanguage::Canada => "Canada",
+            SubLanguage::Caribbean => "Caribbean",
+            SubLanguage::Chile => "Chile",
+            SubLanguage::China => "China",
+            SubLanguage::Colombia => "Colombia",
+            SubLanguage::CostaRica => "Costa Rica",
+            SubLanguage::Cyrillic => "Cyrillic",
+            SubLanguage::DominicanRepublic => "Dominican Republic",
+            SubLanguage::Ecuador => "Ecuador",
+            SubLanguage::Egypt => "Egypt",
+            SubLanguage::ElSalvador => "El Salvador",
+            SubLanguage::Finland => "Finland",
+            SubLanguage::Guatemala => "Guatemala",
+            SubLanguage::Honduras => "Honduras",
+            SubLanguage::HongKong => "Hong Kong",
+            SubLanguage::India => "India",
+            SubLanguage::Iraq => "Iraq",
+            SubLanguage::Ireland => "Ireland",
+            SubLanguage::Jamaica => "Jamaica",
+            SubLanguage::Johab => "Johab",
+            SubLanguage::Jordan => "Jordan",
+            SubLanguage::Kuwait => "Kuwait",
+            SubLanguage::Latin => "Latin",
+            SubLanguage::Lebanon => "Lebanon",
+            SubLanguage::Libya => "Libya",
+            SubLanguage::Liechtenstein => "Liechtenstein",
+            SubLanguage::Luxembourg => "Luxembourg",
+            SubLanguage::Macau => "Macau",
+            SubLanguage::Malaysia => "Malaysia",
+            SubLanguage::Mexico => "Mexico",
+            SubLanguage::Monaco => "Monaco",
+            SubLanguage::Morocco => "Morocco",
+            SubLanguage::Netherlands => "Netherlands",
+            SubLanguage::NewZealand => "New Zealand",
+            SubLanguage::Nicaragua => "Nicaragua",
+            SubLanguage::Nynorsk => "Nynorsk",
+            SubLanguage::Oman => "Oman",
+            SubLanguage::Pakistan => "Pakistan",
+            SubLanguage::Panama => "Panama",
+            SubLanguage::Paraguay => "Paraguay",
+            SubLanguage::Peru => "Peru",
+            SubLanguage::Philippines => "Philippines",
+            SubLanguage::PuertoRico => "Puerto Rico",
+            SubLanguage::Qatar => "Qatar",
+            SubLanguage::SaudiArabia => "Saudi Arabia",
+            SubLanguage::Singapore => "Singapore",
+            SubLanguage::SouthAfrica => "South Africa",
+            SubLanguage::Switzerland => "Switzerland",
+            SubLanguage::Syria => "Syria",
+            SubLanguage::Taiwan => "Taiwan",
+            SubLanguage::Trinidad => "Trinidad",
+            SubLanguage::Tunisia => "Tunisia",
+            SubLanguage::UnitedArabEmirates => "United Arab Emirates",
+            SubLanguage::UnitedKingdom => "United Kingdom",
+            SubLanguage::UnitedStates => "United States",
+            SubLanguage::Uruguay => "Uruguay",
+            SubLanguage::Venezuela => "Venezuela",
+            SubLanguage::Yemen => "Yemen",
+            SubLanguage::Zimbabwe => "Zimbabwe",
+            SubLanguage::UsageDataDescriptor => "Usage Data Descriptor",
+            SubLanguage::VendorDefined1 => "Vendor Defined 1",
+            SubLanguage::VendorDefined2 => "Vendor Defined 2",
+            SubLanguage::VendorDefined3 => "Vendor Defined 3",
+            SubLanguage::VendorDefined4 => "Vendor Defined 4",
+            SubLanguage::Other(_) => "Unknown",
+        }
+    }
+
     fn from_raw(language: PrimaryLanguage, raw: u16) -> SubLanguage {
         match language {
             PrimaryLanguage::Arabic => match raw & SUB_LANGUAGE_MASK {
@@ -451,7 +756,7 @@ impl SubLanguage {
 
 #[cfg(test)]
 mod test {
-    use super::{PrimaryLanguage, SubLanguage};
+    use super::{Language, PrimaryLanguage, SubLanguage};
     use super::{PRIMARY_LANGUAGE_MASK, SUB_LANGUAGE_MASK};
 
     // language ids defined in http://www.usb.org/developers/docs/USB_LANGIDs.pdf
@@ -2540,6 +2845,75 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_creates_language_from_primary_and_sub_language() {
+        assert_eq!(
+            Language::new(PrimaryLanguage::English, SubLanguage::UnitedStates)
+                .map(Language::lang_id),
+            Some(ENGLISH_UNITED_STATES)
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::Serbian, SubLanguage::Cyrillic).map(Language::lang_id),
+            Some(SERBIAN_CYRILLIC)
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::Croatian, SubLanguage::Standard).map(Language::lang_id),
+            Some(CROATIAN)
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::Afrikaans, SubLanguage::Standard).map(Language::lang_id),
+            Some(AFRIKAANS)
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::HID, SubLanguage::VendorDefined4).map(Language::lang_id),
+            Some(HID_VENDOR_DEFINED_4)
+        );
+    }
+
+    #[test]
+    fn it_rejects_undefined_language_pairs() {
+        assert_eq!(
+            Language::new(PrimaryLanguage::English, SubLanguage::Bokmal),
+            None
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::Serbian, SubLanguage::Standard),
+            None
+        );
+        assert_eq!(
+            Language::new(PrimaryLanguage::Other(0x0009), SubLanguage::UnitedStates),
+            None
+        );
+    }
+
+    #[test]
+    fn it_round_trips_every_lang_id_through_new() {
+        for lang_id in 0..=u16::MAX {
+            let language = Language::from_lang_id(lang_id);
+            let rebuilt = Language::new(language.primary_language(), language.sub_language())
+                .expect("pair was decoded from a LANGID");
+
+            assert_eq!(rebuilt.primary_language(), language.primary_language());
+            assert_eq!(rebuilt.sub_language(), language.sub_language());
+        }
+    }
+
+    #[test]
+    fn it_hashes_languages_by_lang_id() {
+        use std::collections::HashSet;
+
+        let languages: HashSet<Language> = [
+            Language::from_lang_id(ENGLISH_UNITED_STATES),
+            Language::new(PrimaryLanguage::English, SubLanguage::UnitedStates).unwrap(),
+            Language::from_lang_id(ENGLISH_UNITED_KINGDOM),
+        ]
+        .iter()
+        .copied()
+        .collect();
+
+        assert_eq!(languages.len(), 2);
+    }
+
     #[test]
     fn it_recognizes_other_as_other_language() {
         assert_eq!(
@@ -2555,4 +2929,49 @@ mod test {
             SubLanguage::Other(SUB_LANGUAGE_MASK)
         );
     }
+
+    #[test]
+    fn it_names_languages() {
+        assert_eq!(
+            "English (United States)",
+            Language::from_lang_id(ENGLISH_UNITED_STATES).name()
+        );
+        assert_eq!(
+            "Chinese (Hong Kong)",
+            Language::from_lang_id(CHINESE_HONG_KONG).name()
+        );
+        assert_eq!(
+            "Spanish (Traditional)",
+            Language::from_lang_id(SPANISH_TRADITIONAL_SORT).name()
+        );
+        assert_eq!("German", Language::from_lang_id(GERMAN_STANDARD).name());
+        assert_eq!("Japanese", Language::from_lang_id(JAPANESE).name());
+        assert_eq!(
+            "HID (Vendor Defined 1)",
+            Language::from_lang_id(HID_VENDOR_DEFINED_1).name()
+        );
+    }
+
+    #[test]
+    fn it_names_unknown_languages_by_lang_id() {
+        assert_eq!("Unknown (0x0000)", Language::from_lang_id(0x0000).name());
+        assert_eq!("English (0x7C09)", Language::from_lang_id(0x7C09).name());
+    }
+
+    #[test]
+    fn it_names_primary_languages() {
+        assert_eq!("Afrikaans", PrimaryLanguage::Afrikaans.name());
+        assert_eq!("HID", PrimaryLanguage::HID.name());
+        assert_eq!("Unknown", PrimaryLanguage::Other(0x0000).name());
+    }
+
+    #[test]
+    fn it_names_sub_languages() {
+        assert_eq!(
+            "United Arab Emirates",
+            SubLanguage::UnitedArabEmirates.name()
+        );
+        assert_eq!("Brunei Darussalam", SubLanguage::BruneiDarussalam.name());
+        assert_eq!("Unknown", SubLanguage::Other(0x7C00).name());
+    }
 }
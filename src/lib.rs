@@ -3,27 +3,42 @@
 pub use libusb1_sys as ffi;
 pub use libusb1_sys::constants;
 
+#[cfg(not(feature = "no-global-context"))]
+pub use crate::context::GlobalContext;
 #[cfg(unix)]
 pub use crate::options::disable_device_discovery;
 pub use crate::{
-    config_descriptor::{ConfigDescriptor, Interfaces},
-    context::{Context, GlobalContext, LogCallbackMode, LogLevel, UsbContext},
+    bulk_frames::BulkFrames,
+    config_descriptor::{ConfigDescriptor, InterfaceAssociation, Interfaces},
+    context::{Context, EventThreadGuard, LogCallbackMode, LogLevel, UsbContext},
+    descriptor_builder::{
+        ConfigDescriptorBuilder, DeviceDescriptorBuilder, EndpointDescriptorBuilder,
+        InterfaceDescriptorBuilder,
+    },
     device::Device,
     device_descriptor::DeviceDescriptor,
-    device_handle::DeviceHandle,
-    device_list::{DeviceList, Devices},
-    endpoint_descriptor::EndpointDescriptor,
-    error::{Error, Result},
+    device_handle::{BufKind, DeviceHandle, InterfaceGuard, ResetOutcome, TransferStats},
+    device_list::{DeviceList, DevicePoll, Devices},
+    dfu::{DfuState, DfuStatus},
+    dma_buffer::DmaBuffer,
+    endpoint_descriptor::{EndpointDescriptor, OwnedEndpointInfo},
+    error::{Error, Result, SequenceError},
     fields::{
-        request_type, Direction, Recipient, RequestType, Speed, SyncType, TransferType, UsageType,
-        Version,
+        descriptor_value, interface_index, le_u16, le_u16_at, request_type, ControlSetup,
+        DeviceStatus, Direction, ParseFieldError, Recipient, RequestType, Speed, SyncType,
+        TransferType, UsageType, Version,
+    },
+    hotplug::{
+        ContextHotplug, Hotplug, HotplugArrival, HotplugBuilder, HotplugEvent, HotplugWithContext,
+        Registration,
     },
-    hotplug::{Hotplug, HotplugBuilder, Registration},
     interface_descriptor::{
-        EndpointDescriptors, Interface, InterfaceDescriptor, InterfaceDescriptors,
+        EndpointDescriptors, HidDescriptor, Interface, InterfaceDescriptor, InterfaceDescriptors,
     },
     language::{Language, PrimaryLanguage, SubLanguage},
     options::UsbOption,
+    port_path::PortPath,
+    usb_io::UsbIo,
     version::{version, LibraryVersion},
 };
 
@@ -35,12 +50,17 @@ mod test_helpers;
 mod error;
 mod version;
 
+mod bulk_frames;
 mod context;
 mod device;
 mod device_handle;
 mod device_list;
+mod dfu;
+mod dma_buffer;
+mod hid;
 
 mod config_descriptor;
+mod descriptor_builder;
 mod device_descriptor;
 mod endpoint_descriptor;
 mod fields;
@@ -48,26 +68,33 @@ mod hotplug;
 mod interface_descriptor;
 mod language;
 mod options;
+mod port_path;
+mod transfer;
+mod usb_io;
 
 /// Tests whether the running `libusb` library supports capability API.
+#[cfg(not(feature = "no-global-context"))]
 pub fn has_capability() -> bool {
     GlobalContext::default().as_raw();
     unsafe { libusb1_sys::libusb_has_capability(constants::LIBUSB_CAP_HAS_CAPABILITY) != 0 }
 }
 
 /// Tests whether the running `libusb` library supports hotplug.
+#[cfg(not(feature = "no-global-context"))]
 pub fn has_hotplug() -> bool {
     GlobalContext::default().as_raw();
     unsafe { libusb1_sys::libusb_has_capability(constants::LIBUSB_CAP_HAS_HOTPLUG) != 0 }
 }
 
 /// Tests whether the running `libusb` library has HID access.
+#[cfg(not(feature = "no-global-context"))]
 pub fn has_hid_access() -> bool {
     GlobalContext::default().as_raw();
     unsafe { libusb1_sys::libusb_has_capability(constants::LIBUSB_CAP_HAS_HID_ACCESS) != 0 }
 }
 
 /// Tests whether the running `libusb` library supports detaching the kernel driver.
+#[cfg(not(feature = "no-global-context"))]
 pub fn supports_detach_kernel_driver() -> bool {
     GlobalContext::default().as_raw();
     unsafe {
@@ -75,12 +102,28 @@ pub fn supports_detach_kernel_driver() -> bool {
     }
 }
 
+/// Tests whether the running `libusb` library can wrap an existing file descriptor with
+/// [`UsbContext::open_device_with_fd`].
+///
+/// libusb has no capability for this, so the answer is derived from the platform and the
+/// library version: wrapping is implemented by the Linux backend, which Android also uses,
+/// since `libusb` 1.0.23.
+pub fn supports_wrap_sys_device() -> bool {
+    if !cfg!(any(target_os = "linux", target_os = "android")) {
+        return false;
+    }
+
+    version().at_least(1, 0, 23)
+}
+
 /// Returns a list of the current USB devices. Using global context
+#[cfg(not(feature = "no-global-context"))]
 pub fn devices() -> crate::Result<DeviceList<GlobalContext>> {
     GlobalContext::default().devices()
 }
 
 /// Sets the log level of a `libusb` global context.
+#[cfg(not(feature = "no-global-context"))]
 pub fn set_log_level(level: LogLevel) {
     unsafe {
         libusb1_sys::libusb_set_debug(GlobalContext::default().as_raw(), level.as_c_int());
@@ -96,6 +139,7 @@ pub fn set_log_level(level: LogLevel) {
 ///
 /// Returns a device handle for the first device found matching `vendor_id` and `product_id`.
 /// On error, or if the device could not be found, it returns `None`.
+#[cfg(not(feature = "no-global-context"))]
 pub fn open_device_with_vid_pid(
     vendor_id: u16,
     product_id: u16,
@@ -19,16 +19,60 @@ impl UsbOption {
         }
     }
 
+    /// Skip the operations that need elevated privileges, as on Android without root.
+    ///
+    /// This is `LIBUSB_OPTION_WEAK_AUTHORITY`, which newer `libusb` versions call
+    /// `LIBUSB_OPTION_NO_DEVICE_DISCOVERY`: `libusb` doesn't scan for devices, and they are opened
+    /// from file descriptors with [`open_device_with_fd`] instead. Only the Linux backend, which
+    /// Android uses as well, supports it; elsewhere applying it fails with `NotSupported`.
+    ///
+    /// `libusb` reads the option during initialization, so it only fully takes effect on a
+    /// context if it was set before that context was created, e.g. process-wide with
+    /// [`disable_device_discovery`]. [`Context::with_options`] can only apply it after
+    /// `libusb_init`, when the initial device scan, and any failure to access the devices it
+    /// ran into, has already happened.
+    ///
+    /// [`Context::with_options`]: crate::Context::with_options
+    ///
+    /// [`open_device_with_fd`]: crate::UsbContext::open_device_with_fd
+    /// [`disable_device_discovery`]: crate::disable_device_discovery
+    #[doc(alias = "LIBUSB_OPTION_WEAK_AUTHORITY")]
+    pub fn weak_authority() -> Self {
+        Self {
+            inner: OptionInner::WeakAuthority,
+        }
+    }
+
+    /// Sets the `libusb` option with the given `LIBUSB_OPTION_*` code, without any argument.
+    ///
+    /// This gives access to options that were added to `libusb` after this version of `rusb`,
+    /// before they get a dedicated constructor. Prefer the dedicated constructors where they
+    /// exist. An option the loaded `libusb` doesn't know fails with `InvalidParam` or
+    /// `NotSupported` when the option is applied.
+    ///
+    /// ## Safety
+    ///
+    /// `libusb_set_option` is variadic, and this passes no arguments after the option code. The
+    /// option must be one that takes no arguments, otherwise `libusb` reads an argument that was
+    /// never passed.
+    pub unsafe fn raw(option: u32) -> Self {
+        Self {
+            inner: OptionInner::Raw(option),
+        }
+    }
+
     pub(crate) fn apply<T: UsbContext>(&self, ctx: &mut T) -> crate::Result<()> {
-        match self.inner {
-            OptionInner::UseUsbdk => {
-                let err = unsafe { libusb_set_option(ctx.as_raw(), LIBUSB_OPTION_USE_USBDK) };
-                if err == LIBUSB_SUCCESS {
-                    Ok(())
-                } else {
-                    Err(error::from_libusb(err))
-                }
-            }
+        let option = match self.inner {
+            OptionInner::UseUsbdk => LIBUSB_OPTION_USE_USBDK,
+            OptionInner::WeakAuthority => LIBUSB_OPTION_WEAK_AUTHORITY,
+            OptionInner::Raw(option) => option,
+        };
+
+        let err = unsafe { libusb_set_option(ctx.as_raw(), option) };
+        if err == LIBUSB_SUCCESS {
+            Ok(())
+        } else {
+            Err(error::from_libusb(err))
         }
     }
 }
@@ -36,6 +80,8 @@ impl UsbOption {
 enum OptionInner {
     #[cfg_attr(not(windows), allow(dead_code))] // only constructed on Windows
     UseUsbdk,
+    WeakAuthority,
+    Raw(u32),
 }
 
 /// Disable device scanning in `libusb` init.
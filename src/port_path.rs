@@ -0,0 +1,89 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The physical location of a device: its bus number and the chain of hub ports leading to it.
+///
+/// Unlike the device address, which changes whenever a device is reconnected, the port path
+/// stays the same as long as the device is plugged into the same port. This makes it suitable
+/// for telling apart identical devices, or for assigning roles to whatever is connected to a
+/// given port.
+///
+/// Port paths are ordered by bus number first and then by port numbers, which sorts devices by
+/// their position in the USB tree. Read it with [`Device::port_path`].
+///
+/// [`Device::port_path`]: crate::Device::port_path
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PortPath {
+    bus_number: u8,
+    port_numbers: Vec<u8>,
+}
+
+impl PortPath {
+    /// Creates a port path from a bus number and the port numbers from the root hub down.
+    pub fn new(bus_number: u8, port_numbers: Vec<u8>) -> Self {
+        PortPath {
+            bus_number,
+            port_numbers,
+        }
+    }
+
+    /// Returns the number of the bus.
+    pub fn bus_number(&self) -> u8 {
+        self.bus_number
+    }
+
+    /// Returns the port numbers from the root hub down to the device. This is empty for a root
+    /// hub.
+    pub fn port_numbers(&self) -> &[u8] {
+        &self.port_numbers
+    }
+}
+
+/// Formats the port path the way Linux names USB devices in sysfs, e.g. `3-1.4.2` for port 2
+/// of the hub in port 4 of the hub in port 1 of bus 3. A root hub is formatted as `usb3`.
+impl fmt::Display for PortPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (first, rest) = match self.port_numbers.split_first() {
+            Some(split) => split,
+            None => return write!(f, "usb{}", self.bus_number),
+        };
+
+        write!(f, "{}-{}", self.bus_number, first)?;
+        for port in rest {
+            write!(f, ".{}", port)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PortPath;
+
+    #[test]
+    fn it_formats_like_sysfs() {
+        assert_eq!("3-1.4.2", PortPath::new(3, vec![1, 4, 2]).to_string());
+        assert_eq!("1-7", PortPath::new(1, vec![7]).to_string());
+        assert_eq!("usb2", PortPath::new(2, vec![]).to_string());
+    }
+
+    #[test]
+    fn it_orders_by_position_in_the_tree() {
+        let mut paths = [
+            PortPath::new(3, vec![1]),
+            PortPath::new(1, vec![2, 1]),
+            PortPath::new(1, vec![2]),
+            PortPath::new(1, vec![10]),
+        ];
+        paths.sort();
+
+        assert_eq!(
+            vec!["1-2", "1-2.1", "1-10", "3-1"],
+            paths.iter().map(|p| p.to_string()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,5 +1,20 @@
 pub use std::ptr;
 
+/// Evaluates to the context returned by `$new`, or skips the test if `libusb` can't be
+/// initialized, which happens without access to usbfs, e.g. in some containers. The skip is
+/// reported on stderr rather than passing silently.
+macro_rules! context_or_skip {
+    ($new:expr) => {
+        match $new {
+            Ok(context) => context,
+            Err(e) => {
+                eprintln!("skipping test, libusb can't be initialized: {}", e);
+                return;
+            }
+        }
+    };
+}
+
 macro_rules! merge {
     ($default:expr => $($field:ident : $value:expr),*) => {
         {
@@ -162,3 +177,16 @@ macro_rules! device_descriptor {
         )
     }
 }
+
+/// Returns a read function that hands out `results` in order, copying each one's data into the
+/// buffer, and times out once they are exhausted.
+pub fn reads(
+    mut results: Vec<crate::Result<Vec<u8>>>,
+) -> impl FnMut(&mut [u8]) -> crate::Result<usize> {
+    results.reverse();
+    move |buf| {
+        let data = results.pop().unwrap_or(Err(crate::Error::Timeout))?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+}
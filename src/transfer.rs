@@ -0,0 +1,214 @@
+use std::{convert::TryFrom, marker::PhantomData, ptr::NonNull, thread, time::Duration};
+
+use libc::{c_int, c_uint, c_void};
+use libusb1_sys::{constants::*, *};
+
+use crate::{context, Error};
+
+/// A single asynchronous libusb transfer, driven from the calling thread.
+///
+/// The synchronous transfer functions of libusb block until the transfer ends. This wrapper
+/// submits the transfer and handles events in steps, so the caller can act between them, for
+/// example to cancel the transfer. A transfer that is still in flight when dropped is cancelled
+/// and waited for, so libusb never writes to a buffer that was released. If handling events
+/// keeps failing, dropping it keeps retrying until the transfer has ended.
+pub(crate) struct Transfer<'a> {
+    transfer: NonNull<libusb_transfer>,
+    context: *mut libusb_context,
+    completed: Box<c_int>,
+    submitted: bool,
+    _buffer: PhantomData<&'a mut [u8]>,
+}
+
+extern "system" fn transfer_callback(transfer: *mut libusb_transfer) {
+    unsafe {
+        *((*transfer).user_data as *mut c_int) = 1;
+    }
+}
+
+impl<'a> Transfer<'a> {
+    /// Allocates a bulk transfer on `endpoint` reading into or writing from `buffer`.
+    ///
+    /// `buffer` is only written to if `endpoint` is an IN endpoint.
+    pub(crate) fn bulk(
+        context: *mut libusb_context,
+        handle: *mut libusb_device_handle,
+        endpoint: u8,
+        buffer: &'a mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<Transfer<'a>> {
+        Self::new_bulk(
+            context,
+            handle,
+            endpoint,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            timeout,
+        )
+    }
+
+    /// Allocates a bulk transfer writing `buffer` to the OUT endpoint `endpoint`.
+    pub(crate) fn bulk_out(
+        context: *mut libusb_context,
+        handle: *mut libusb_device_handle,
+        endpoint: u8,
+        buffer: &'a [u8],
+        timeout: Duration,
+    ) -> crate::Result<Transfer<'a>> {
+        if endpoint & LIBUSB_ENDPOINT_DIR_MASK != LIBUSB_ENDPOINT_OUT {
+            return Err(Error::InvalidParam);
+        }
+
+        // libusb only reads from the buffer of an OUT transfer.
+        Self::new_bulk(
+            context,
+            handle,
+            endpoint,
+            buffer.as_ptr() as *mut u8,
+            buffer.len(),
+            timeout,
+        )
+    }
+
+    fn new_bulk(
+        context: *mut libusb_context,
+        handle: *mut libusb_device_handle,
+        endpoint: u8,
+        buffer: *mut u8,
+        len: usize,
+        timeout: Duration,
+    ) -> crate::Result<Transfer<'a>> {
+        // libusb takes the length as a C int.
+        let len = c_int::try_from(len).map_err(|_| Error::InvalidParam)?;
+        let transfer = NonNull::new(unsafe { libusb_alloc_transfer(0) }).ok_or(Error::NoMem)?;
+        let mut completed = Box::new(0);
+
+        unsafe {
+            libusb_fill_bulk_transfer(
+                transfer.as_ptr(),
+                handle,
+                endpoint,
+                buffer,
+                len,
+                transfer_callback,
+                &mut *completed as *mut c_int as *mut c_void,
+                timeout.as_millis() as c_uint,
+            );
+        }
+
+        Ok(Transfer {
+            transfer,
+            context,
+            completed,
+            submitted: false,
+            _buffer: PhantomData,
+        })
+    }
+
+    /// Submits the transfer to the device.
+    pub(crate) fn submit(&mut self) -> crate::Result<()> {
+        *self.completed = 0;
+        try_unsafe!(libusb_submit_transfer(self.transfer.as_ptr()));
+        self.submitted = true;
+        Ok(())
+    }
+
+    /// Returns true if the transfer was submitted and has ended.
+    pub(crate) fn is_completed(&self) -> bool {
+        *self.completed != 0
+    }
+
+    /// Handles events for at most `timeout` and returns whether the transfer has ended.
+    pub(crate) fn poll(&mut self, timeout: Duration) -> crate::Result<bool> {
+        if !self.submitted || self.is_completed() {
+            return Ok(true);
+        }
+
+        context::handle_events_completed(self.context, &mut *self.completed, Some(timeout))?;
+        Ok(self.is_completed())
+    }
+
+    /// Requests cancellation of the transfer. It ends with a cancelled status unless it
+    /// completed in the meantime.
+    pub(crate) fn cancel(&mut self) {
+        if self.submitted && !self.is_completed() {
+            unsafe {
+                libusb_cancel_transfer(self.transfer.as_ptr());
+            }
+        }
+    }
+
+    /// Handles events until the transfer has ended.
+    ///
+    /// If event handling fails, the transfer is cancelled and still waited for before the
+    /// error is returned. If that fails as well, the error is returned while the transfer may
+    /// still be in flight; dropping it then waits for it to end.
+    pub(crate) fn wait(&mut self) -> crate::Result<()> {
+        let mut result = Ok(());
+
+        while self.submitted && !self.is_completed() {
+            if let Err(e) =
+                context::handle_events_completed(self.context, &mut *self.completed, None)
+            {
+                if result.is_err() {
+                    break;
+                }
+                self.cancel();
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+
+    /// Returns the number of bytes transferred.
+    pub(crate) fn actual_length(&self) -> usize {
+        unsafe { (*self.transfer.as_ptr()).actual_length as usize }
+    }
+
+    /// Returns the result of a transfer that has ended.
+    ///
+    /// A transfer that ended with data after a timeout or cancellation counts as successful,
+    /// the same as for the synchronous transfer functions.
+    pub(crate) fn result(&self) -> crate::Result<usize> {
+        let transferred = self.actual_length();
+
+        match self.status() {
+            Ok(()) => Ok(transferred),
+            Err(Error::Timeout) | Err(Error::Interrupted) if transferred > 0 => Ok(transferred),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the status of a transfer that has ended, regardless of the data transferred.
+    pub(crate) fn status(&self) -> crate::Result<()> {
+        let status = unsafe { (*self.transfer.as_ptr()).status };
+
+        match status {
+            LIBUSB_TRANSFER_COMPLETED => Ok(()),
+            LIBUSB_TRANSFER_TIMED_OUT => Err(Error::Timeout),
+            LIBUSB_TRANSFER_CANCELLED => Err(Error::Interrupted),
+            LIBUSB_TRANSFER_STALL => Err(Error::Pipe),
+            LIBUSB_TRANSFER_NO_DEVICE => Err(Error::NoDevice),
+            LIBUSB_TRANSFER_OVERFLOW => Err(Error::Overflow),
+            _ => Err(Error::Io),
+        }
+    }
+}
+
+impl<'a> Drop for Transfer<'a> {
+    fn drop(&mut self) {
+        // The borrowed buffer is released after this, so the transfer has to end first, however
+        // often handling events fails.
+        while self.submitted && !self.is_completed() {
+            self.cancel();
+            if self.wait().is_err() && !self.is_completed() {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        unsafe {
+            libusb_free_transfer(self.transfer.as_ptr());
+        }
+    }
+}
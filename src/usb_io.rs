@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::{device_handle::DeviceHandle, UsbContext};
+
+/// Synchronous I/O operations on an open device.
+///
+/// [`DeviceHandle`] implements this trait by forwarding to its inherent methods of the same name.
+/// Driver code that is generic over `UsbIo` can be exercised against a mock implementation in
+/// tests, without real hardware.
+///
+/// See the corresponding [`DeviceHandle`] methods for the meaning of each argument and the errors
+/// that can be returned.
+pub trait UsbIo {
+    /// Reads from an interrupt endpoint. See [`DeviceHandle::read_interrupt`].
+    fn read_interrupt(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize>;
+
+    /// Writes to an interrupt endpoint. See [`DeviceHandle::write_interrupt`].
+    fn write_interrupt(&self, endpoint: u8, buf: &[u8], timeout: Duration) -> crate::Result<usize>;
+
+    /// Reads from a bulk endpoint. See [`DeviceHandle::read_bulk`].
+    fn read_bulk(&self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> crate::Result<usize>;
+
+    /// Writes to a bulk endpoint. See [`DeviceHandle::write_bulk`].
+    fn write_bulk(&self, endpoint: u8, buf: &[u8], timeout: Duration) -> crate::Result<usize>;
+
+    /// Reads data using a control transfer. See [`DeviceHandle::read_control`].
+    fn read_control(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize>;
+
+    /// Writes data using a control transfer. See [`DeviceHandle::write_control`].
+    fn write_control(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize>;
+}
+
+impl<T: UsbContext> UsbIo for DeviceHandle<T> {
+    fn read_interrupt(
+        &self,
+        endpoint: u8,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        DeviceHandle::read_interrupt(self, endpoint, buf, timeout)
+    }
+
+    fn write_interrupt(&self, endpoint: u8, buf: &[u8], timeout: Duration) -> crate::Result<usize> {
+        DeviceHandle::write_interrupt(self, endpoint, buf, timeout)
+    }
+
+    fn read_bulk(&self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> crate::Result<usize> {
+        DeviceHandle::read_bulk(self, endpoint, buf, timeout)
+    }
+
+    fn write_bulk(&self, endpoint: u8, buf: &[u8], timeout: Duration) -> crate::Result<usize> {
+        DeviceHandle::write_bulk(self, endpoint, buf, timeout)
+    }
+
+    fn read_control(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        DeviceHandle::read_control(self, request_type, request, value, index, buf, timeout)
+    }
+
+    fn write_control(
+        &self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &[u8],
+        timeout: Duration,
+    ) -> crate::Result<usize> {
+        DeviceHandle::write_control(self, request_type, request, value, index, buf, timeout)
+    }
+}
@@ -28,6 +28,18 @@ impl LibraryVersion {
         self.inner.nano
     }
 
+    /// Returns true if the library is version `major.minor.micro` or newer.
+    ///
+    /// ```
+    /// let version = rusb::version();
+    /// if version.at_least(1, 0, 24) {
+    ///     // use the features added in libusb 1.0.24
+    /// }
+    /// ```
+    pub fn at_least(&self, major: u16, minor: u16, micro: u16) -> bool {
+        (self.major(), self.minor(), self.micro()) >= (major, minor, micro)
+    }
+
     /// Library release candidate suffix string, e.g., `"-rc4"`.
     pub fn rc(&self) -> Option<&'static str> {
         let cstr = unsafe { CStr::from_ptr(self.inner.rc) };